@@ -0,0 +1,185 @@
+use std::{
+	path::{Path, PathBuf},
+	process::{Command, Stdio},
+};
+
+use blink::{config, source::Source, Formatter};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+/// Exit code of a dry run finding files that need formatting
+const EXIT_FORMATTING_NEEDED: i32 = 1;
+
+type Rule = fn(&Formatter, &String) -> String;
+
+/// Every rule switched on, so the benchmarks cover all passes and not only the default ones
+fn formatter() -> Formatter
+{
+	let mut config = config::load(false, true, Path::new(env!("CARGO_MANIFEST_DIR")));
+	config.control_flow_braces = config::ControlFlowBraces::Always;
+	config.function_bodies = config::FunctionBodies::Arrow;
+	config.trailing_commas = config::TrailingCommas::Multiline;
+	config.flutter_widget_trees = true;
+	config.max_chain_length = Some(80);
+	config.organize_imports = true;
+	config.normalize_spacing = true;
+
+	return Formatter { config };
+}
+
+/// The synthetic corpora along with every sample shipped in the repository
+fn corpora() -> Vec<(String, String)>
+{
+	let mut corpora = vec![(String::from("large_class"), large_class(2000)), (String::from("widget_tree"), widget_tree(40, 200)), (String::from("strings"), string_heavy(5000))];
+
+	let samples = Path::new(env!("CARGO_MANIFEST_DIR")).join("samples");
+	for name in ["test1", "test2", "test3"]
+	{
+		if let Ok(content) = std::fs::read_to_string(samples.join(format!("{}.dart", name)))
+		{
+			corpora.push((String::from(name), content));
+		}
+	}
+
+	return corpora;
+}
+
+fn large_class(methods: usize) -> String
+{
+	let mut content = String::from("import 'package:flutter/material.dart';\nimport 'dart:async';\n\nclass Large extends Base implements Other {\n");
+
+	for i in 0..methods
+	{
+		content.push_str(&format!("  final int field{}={};\n", i, i));
+		content.push_str(&format!("  int method{}(int a,int b) {{\n    if(a>b) return a+field{};\n    for (var i=0;i<b;i++) {{\n      a+=i;\n    }}\n    switch (a) {{\n    case 1:\n      return b;\n    default:\n      break;\n    }}\n    return a*b;\n  }}\n\n", i, i));
+		content.push_str(&format!("  int get value{} {{\n    return field{};\n  }}\n\n", i, i));
+	}
+	content.push_str("}\n");
+
+	return content;
+}
+
+fn widget_tree(depth: usize, trees: usize) -> String
+{
+	let mut content = String::from("import 'package:flutter/material.dart';\n\nclass Tree extends StatelessWidget {\n");
+
+	for i in 0..trees
+	{
+		content.push_str(&format!("  Widget build{}(BuildContext context) {{\n    return ", i));
+		for level in 0..depth
+		{
+			content.push_str(&format!("Container(\n{}padding: EdgeInsets.all({}),\n{}child: ", " ".repeat(level + 6), level, " ".repeat(level + 6)));
+		}
+		content.push_str("Text('leaf')");
+		for level in (0..depth).rev()
+		{
+			content.push_str(&format!("\n{})", " ".repeat(level + 4)));
+		}
+		content.push_str(";\n  }\n\n");
+		content.push_str(&format!("  Future<void> load{}() => repository.fetch().then((value) => value.items).where((item) => item.isValid).map((item) => item.name).toList();\n\n", i));
+	}
+	content.push_str("}\n");
+
+	return content;
+}
+
+fn string_heavy(lines: usize) -> String
+{
+	let mut content = String::from("const messages = [\n");
+
+	for i in 0..lines
+	{
+		match i % 4
+		{
+			0 => content.push_str(&format!("  \"message {} with {{braces}} and 'quotes'\",\n", i)),
+			1 => content.push_str(&format!("  'interpolated ${{value{}}} and ${{list.map((e) => e+1)}}',\n", i)),
+			2 => content.push_str(&format!("  r'raw \\n string {} if(x){{}}',\n", i)),
+			_ => content.push_str(&format!("  '''\n  multi line {} {{ not code }}\n  ''',\n", i)),
+		}
+	}
+	content.push_str("];\n");
+
+	return content;
+}
+
+fn bench_format(c: &mut Criterion)
+{
+	let formatter = formatter();
+	let mut group = c.benchmark_group("format");
+
+	for (name, content) in corpora()
+	{
+		group.throughput(Throughput::Bytes(content.len() as u64));
+		group.bench_with_input(BenchmarkId::from_parameter(&name), &content, |b, content| b.iter(|| formatter.format(content.clone())));
+	}
+
+	group.finish();
+}
+
+fn bench_rules(c: &mut Criterion)
+{
+	let formatter = formatter();
+	let rules: [(&str, Rule); 10] = [
+		("control_flow_braces", |f, c| f.add_control_flow_braces(c).0),
+		("function_bodies", |f, c| f.convert_function_bodies(c).0),
+		("arrow_bodies", |f, c| f.indent_arrow_bodies(c).0),
+		("imports", |f, c| f.organize_imports(c).0),
+		("blank_lines", |f, c| f.apply_blank_line_policy(c)),
+		("switches", |f, c| f.correct_switch_indentations(c).0),
+		("widget_trees", |f, c| f.indent_widget_trees(c).0),
+		("method_chains", |f, c| f.format_method_chains(c).0),
+		("trailing_commas", |f, c| f.fix_trailing_commas(c).0),
+		("spacing", |f, c| {
+			let source = Source::new(c);
+			return source.lines.iter().zip(source.states).map(|(line, state)| f.fix_incorrect_spacing(line.to_string(), state).0).collect::<Vec<String>>().join("\n");
+		}),
+	];
+
+	for (name, content) in corpora()
+	{
+		let mut group = c.benchmark_group(format!("rules/{}", name));
+		group.throughput(Throughput::Bytes(content.len() as u64));
+
+		for (rule, run) in rules
+		{
+			group.bench_with_input(BenchmarkId::from_parameter(rule), &content, |b, content| b.iter(|| run(&formatter, content)));
+		}
+
+		group.finish();
+	}
+}
+
+/// Runs the binary on a folder holding every corpus, to include reading, walking and ignores
+fn bench_walker(c: &mut Criterion)
+{
+	let folder = std::env::temp_dir().join(format!("blink-bench-{}", std::process::id()));
+	let nested = folder.join("lib").join("widgets");
+	std::fs::create_dir_all(&nested).unwrap();
+
+	let mut size = 0;
+	for (name, content) in corpora()
+	{
+		for directory in [&folder, &nested]
+		{
+			std::fs::write(directory.join(format!("{}_corpus.dart", name)), &content).unwrap();
+			size += content.len();
+		}
+	}
+
+	let mut group = c.benchmark_group("walker");
+	group.sample_size(10);
+	group.throughput(Throughput::Bytes(size as u64));
+	group.bench_function("dry_run", |b| b.iter(|| run_binary(&folder)));
+	group.finish();
+
+	let _ = std::fs::remove_dir_all(&folder);
+}
+
+fn run_binary(folder: &PathBuf)
+{
+	let status = Command::new(env!("CARGO_BIN_EXE_blink")).arg("-d").arg(folder).stdout(Stdio::null()).stderr(Stdio::null()).status().unwrap();
+	// The corpora aren't formatted, so the dry run reports that they need to be
+	assert!(status.success() || status.code() == Some(EXIT_FORMATTING_NEEDED));
+}
+
+criterion_group!(benches, bench_format, bench_rules, bench_walker);
+criterion_main!(benches);
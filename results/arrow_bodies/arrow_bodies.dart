@@ -0,0 +1,15 @@
+class Shapes
+{
+	final double width = 2;
+
+	double area() => width * width;
+
+	String describe(String name) => "$name is ${area()} wide";
+
+	void log()
+	{
+		print(area());
+	}
+
+	Future<double> load() async => await fetch();
+}
@@ -0,0 +1,21 @@
+import "dart:async";
+class Timer
+{
+
+	final int seconds = 0;
+	final int minutes = 0;
+
+	void start()
+	{
+
+		print("start");
+
+
+		print("started");
+	}
+
+	void stop()
+	{
+		print("stop");
+	}
+}
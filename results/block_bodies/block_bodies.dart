@@ -0,0 +1,27 @@
+class Shapes
+{
+	final double width = 2;
+
+	double area()
+	{
+		return width * width;
+	}
+
+	String describe(String name) =>
+		"$name is ${area()} wide";
+
+	void log()
+	{
+		print(area());
+	}
+
+	double get perimeter
+	{
+		return width * 4;
+	}
+
+	Future<double> load() async
+	{
+		return await fetch();
+	}
+}
@@ -0,0 +1,24 @@
+class Counter {
+	int count = 0;
+
+	void increment(int by) {
+		if (by > 0) {
+			count += by;
+		} else {
+			count = 0;
+		}
+	}
+
+	Future<void> save() async {
+		try {
+			await write(count);
+		} catch (error) {
+			print(error);
+		}
+	}
+}
+
+enum Direction {
+	up,
+	down
+}
@@ -0,0 +1,6 @@
+class Whitespace  
+{
+	final int value = 1;   
+
+	int get doubled => value * 2;	
+}
\ No newline at end of file
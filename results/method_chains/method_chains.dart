@@ -0,0 +1,22 @@
+class Orders
+{
+	List<String> summary(List<Order> orders)
+	{
+		final short = orders.where((o) => o.paid).toList();
+		return orders
+			.where((order) => order.total > 100)
+			.map((order) => order.customer.name)
+			.toSet()
+			.toList();
+	}
+
+	void draw(Canvas canvas)
+	{
+		final paint = Paint()
+			..color = Colors.blue
+			..strokeWidth = 4
+			..style = PaintingStyle.stroke
+			..isAntiAlias = true;
+		canvas.drawCircle(Offset.zero, 10, paint);
+	}
+}
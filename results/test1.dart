@@ -1,4 +1,4 @@
-import 'dart:convert';
+import "dart:convert";
 
 class Widget
 {
@@ -104,14 +104,14 @@ class TestClass extends TestSuperClass
 
 	void switches1()
 	{
-		switch(animal)
+		switch (animal)
 		{
 			case Animal.Cat: break;
 			case Animal.Dog: break;
 			case Animal.Gerbil: break;
 		}
 
-		switch(animal)
+		switch (animal)
 		{
 			case Animal.Cat:
 			break;
@@ -121,7 +121,7 @@ class TestClass extends TestSuperClass
 			break;
 		}
 
-		switch(animal)
+		switch (animal)
 		{
 			case Animal.Cat:
 			break;
@@ -134,14 +134,14 @@ class TestClass extends TestSuperClass
 
 	void switches2()
 	{
-		switch(animal)
+		switch (animal)
 		{
 			case Animal.Cat: {} break;
 			case Animal.Dog: {} break;
 			case Animal.Gerbil: {} break;
 		}
 
-		switch(animal)
+		switch (animal)
 		{
 			case Animal.Cat:
 			{
@@ -157,7 +157,7 @@ class TestClass extends TestSuperClass
 			break;
 		}
 
-		switch(animal)
+		switch (animal)
 		{
 			case Animal.Cat:
 			{
@@ -176,10 +176,10 @@ class TestClass extends TestSuperClass
 
 	void switchWithinSwitch()
 	{
-		switch(animal)
+		switch (animal)
 		{
 			case Animal.Cat:
-				switch(cat)
+				switch (cat)
 				{
 					case CatRace.Meow1:
 					break;
@@ -190,7 +190,7 @@ class TestClass extends TestSuperClass
 				}
 			break;
 			case Animal.Dog:
-				switch(dog)
+				switch (dog)
 				{
 					case DogRace.Woof1: break;
 					case DogRace.Woof2: break;
@@ -198,7 +198,7 @@ class TestClass extends TestSuperClass
 				}
 			break;
 			case Animal.Gerbil:
-				switch(gerbil)
+				switch (gerbil)
 				{
 					case GerbilRace.Gerb1:
 					break;
@@ -206,7 +206,7 @@ class TestClass extends TestSuperClass
 					break;
 					case GerbilRace.Gerb3:
 					{
-						}
+					}
 					break;
 				}
 			break;
@@ -215,12 +215,12 @@ class TestClass extends TestSuperClass
 
 	void breakInForLoopWithinSwitch()
 	{
-		switch(animal)
+		switch (animal)
 		{
 			case Animal.Cat:
-				for (var i = 0; i<5; i++)
+				for (var i = 0; i < 5; i++)
 					if (i == 2)
-			break; // TODO: This is incorrecly adjusted when it shouldnt be
+						break; // TODO: This is incorrecly adjusted when it shouldnt be
 			break;
 			case Animal.Dog:
 			break;
@@ -82,14 +82,14 @@ class TestClass extends TestSuperClass
 
 	void switches1()
 	{
-		switch(animal)
+		switch (animal)
 		{
 			case Animal.Cat: break;
 			case Animal.Dog: break;
 			case Animal.Gerbil: break;
 		}
 
-		switch(animal)
+		switch (animal)
 		{
 			case Animal.Cat:
 			break;
@@ -99,7 +99,7 @@ class TestClass extends TestSuperClass
 			break;
 		}
 
-		switch(animal)
+		switch (animal)
 		{
 			case Animal.Cat:
 			break;
@@ -112,14 +112,14 @@ class TestClass extends TestSuperClass
 
 	void switches2()
 	{
-		switch(animal)
+		switch (animal)
 		{
 			case Animal.Cat: {} break;
 			case Animal.Dog: {} break;
 			case Animal.Gerbil: {} break;
 		}
 
-		switch(animal)
+		switch (animal)
 		{
 			case Animal.Cat:
 			{
@@ -135,7 +135,7 @@ class TestClass extends TestSuperClass
 			break;
 		}
 
-		switch(animal)
+		switch (animal)
 		{
 			case Animal.Cat:
 			{
@@ -0,0 +1,46 @@
+import "package:flutter/material.dart";
+
+class TrailingCommas
+{
+	final List<int> numbers = [
+		1,
+		2,
+		3,
+	];
+
+	final Map<String, int> scores = {
+		"a": 1,
+		"b": 2,
+	};
+
+	final single = [1, 2, 3,];
+
+	int sum() => (
+		numbers[0] + numbers[1]
+	);
+
+	bool isLarger(int a, int b) => a > (
+		b + 1
+	);
+
+	List<int> parse(String value) => decode<List<int>>(
+		value,
+		strict: true,
+	);
+
+	void log(String message, {int level = 0})
+	{
+		print(
+			message,
+		);
+		debugPrint(
+			"$level: $message",
+			wrapWidth: 80,
+		);
+	}
+
+	(int, int) pair() => (
+		1,
+		2,
+	);
+}
@@ -0,0 +1,9 @@
+import "widgets/button.dart";
+import "package:flutter/material.dart";
+import "dart:async";
+export "src/models.dart";
+import "dart:io";
+
+class Screen
+{
+}
@@ -0,0 +1,24 @@
+class Shapes
+{
+	final double width = 2;
+
+	double area()
+	{
+		return width * width;
+	}
+
+	String describe(String name)
+	{
+		return "$name is ${area()} wide";
+	}
+
+	void log()
+	{
+		print(area());
+	}
+
+	Future<double> load() async
+	{
+		return await fetch();
+	}
+}
@@ -0,0 +1,18 @@
+class Handlers
+{
+	final Map<String, int> limits = {
+		"low": 1,
+		"high": 10
+	};
+
+	void register(List<int> values)
+	{
+		values.forEach((value) {
+			print(value);
+		});
+		final doubled = values.map((value) {
+			return value * 2;
+		});
+		final names = {"a", "b"};
+	}
+}
@@ -0,0 +1,14 @@
+class Loops
+{
+	int sum(List<int> values)
+	{
+		var total = 0;
+		for (final value in values)
+			total += value;
+		if (total > 100) return 100;
+		else if (total < 0)
+			return 0;
+		while (total % 2 != 0) total++;
+		return total;
+	}
+}
@@ -0,0 +1,18 @@
+import "package:flutter/material.dart";
+
+class Greeting
+{
+	final String name = "World";
+	final String quoted = "It's a \"test\"";
+	final String mixed = "Say 'hi'";
+	final String raw = r"C:\path";
+
+	String greet() => "Hello $name";
+
+	String describe()
+	{
+		return """
+Multi-line "string" for ${name}
+""";
+	}
+}
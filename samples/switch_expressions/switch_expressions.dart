@@ -0,0 +1,43 @@
+enum Shape { circle, square, triangle }
+
+class Switches
+{
+	int sides(Shape shape)
+	{
+		return switch (shape) { Shape.circle => 0, Shape.square => 4, _ => 3 };
+	}
+
+	String describe(Object value)
+	{
+		final description = switch (value)
+		{
+		int number when number > 10 => "large number",
+		int _ => "number",
+		String text => "text of ${text.length}",
+		_ => "something else",
+		};
+		return description;
+	}
+
+	double area(Shape shape, double size) => switch (shape)
+	{
+			Shape.circle => 3.14 * size * size,
+			Shape.square => size * size,
+			Shape.triangle => size * size / 2,
+	};
+
+	void handle(int code)
+	{
+		switch (code)
+		{
+		case 200:
+			print("ok");
+			break;
+			case 404:
+		print("not found");
+		break;
+		default:
+			print("error");
+		}
+	}
+}
@@ -0,0 +1,11 @@
+class Spacing
+{
+	int total(int a,int b)
+	{
+		if(a>b)
+		{
+			return a+b;
+		}
+		return max (a , b);
+	}
+}
@@ -0,0 +1,21 @@
+import "package:flutter/material.dart";
+
+class Profile extends StatelessWidget
+{
+	@override
+	Widget build(BuildContext context)
+	{
+		return Scaffold(
+		appBar: AppBar(title: Text("Profile")),
+		body: Column(
+		children: [
+		Text("Name"),
+		Padding(
+		padding: EdgeInsets.all(8),
+		child: Text("Bio")
+		)
+		]
+		)
+		);
+	}
+}
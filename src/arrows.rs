@@ -0,0 +1,356 @@
+use crate::config::FunctionBodies;
+use crate::formatter::Formatter;
+use crate::source::Source;
+
+const NON_FUNCTION_KEYWORDS: [&str; 14] = ["if", "for", "while", "switch", "catch", "on", "else", "do", "try", "finally", "return", "case", "await", "yield"];
+
+impl Formatter
+{
+	pub fn convert_function_bodies(&self, content: &String) -> (String, i32)
+	{
+		match self.config.function_bodies
+		{
+			FunctionBodies::Preserve => (content.to_string(), 0),
+			FunctionBodies::Arrow => self.convert_block_bodies_to_arrows(content),
+			FunctionBodies::Block => self.convert_arrow_bodies_to_blocks(content),
+		}
+	}
+
+	/// Turns functions whose body is a single return statement into `=>` functions
+	fn convert_block_bodies_to_arrows(&self, content: &String) -> (String, i32)
+	{
+		let source = Source::new(content);
+		let lines = &source.lines;
+		let masked_lines = &source.masked_lines;
+
+		let mut converted = 0;
+		let mut cleaned_content = String::from("");
+		let mut line_number = 0;
+
+		while line_number < lines.len()
+		{
+			let line = lines[line_number];
+
+			if !source.protected.contains(line_number)
+			{
+				if let Some((return_line, end)) = single_return_body(lines, masked_lines, line_number)
+				{
+					if self.config.verbose
+					{
+						println!("Found function body to convert - {}", line);
+					}
+
+					let masked = masked_lines[line_number].trim_end();
+					let header = masked.strip_suffix('{').unwrap_or(masked).trim_end();
+					let return_code = lines[return_line].trim_start();
+
+					cleaned_content.push_str(&line[..header.len()]);
+					cleaned_content.push_str(" => ");
+					cleaned_content.push_str(return_code["return".len()..].trim_start());
+					cleaned_content.push_str("\n");
+
+					converted += 1;
+					line_number = end + 1;
+					continue;
+				}
+			}
+
+			cleaned_content.push_str(line);
+			cleaned_content.push_str("\n");
+			line_number += 1;
+		}
+
+		return (cleaned_content, converted);
+	}
+
+	/// Turns single line `=>` functions into block bodies holding a return statement
+	fn convert_arrow_bodies_to_blocks(&self, content: &String) -> (String, i32)
+	{
+		let source = Source::new(content);
+		let masked_lines = &source.masked_lines;
+
+		let mut converted = 0;
+		let mut cleaned_content = String::from("");
+
+		for (line_number, line) in source.lines.iter().enumerate()
+		{
+			let arrow = if source.protected.contains(line_number) { None } else { function_arrow(&masked_lines[line_number]) };
+
+			match arrow
+			{
+				Some(arrow) =>
+				{
+					if self.config.verbose
+					{
+						println!("Found function body to convert - {}", line);
+					}
+
+					let header = line[..arrow].trim_end();
+					let expression = line[arrow + 2..].trim();
+					let indentation = &line[..line.len() - line.trim_start().len()];
+
+					cleaned_content.push_str(header);
+					if self.config.curly_brace_on_next_line
+					{
+						cleaned_content.push_str("\n");
+						cleaned_content.push_str(indentation);
+						cleaned_content.push_str("{\n");
+					}
+					else
+					{
+						cleaned_content.push_str(" {\n");
+					}
+
+					let returns_value = !is_void_function(&masked_lines[line_number][..arrow]);
+					let mut statement = String::from(if returns_value { "return " } else { "" });
+					statement.push_str(expression);
+
+					cleaned_content.push_str(self.reindent(&statement, self.indentation_width(line) + self.config.indentation.size.max(1)).as_str());
+					cleaned_content.push_str("\n");
+					cleaned_content.push_str(indentation);
+					cleaned_content.push_str("}\n");
+
+					converted += 1;
+				}
+				None =>
+				{
+					cleaned_content.push_str(line);
+					cleaned_content.push_str("\n");
+				}
+			}
+		}
+
+		return (cleaned_content, converted);
+	}
+
+	/// Indents the lines continuing a `=>` body one level deeper than the line holding the arrow
+	pub fn indent_arrow_bodies(&self, content: &String) -> (String, i32)
+	{
+		let source = Source::new(content);
+		let mut lines = source.owned_lines();
+		let masked_lines = &source.masked_lines;
+
+		let unit = self.config.indentation.size.max(1);
+		let mut incorrect_arrow_indentations = 0;
+
+		for line_number in 0..lines.len()
+		{
+			if source.protected.contains(line_number) || !masked_lines[line_number].trim_end().ends_with("=>")
+			{
+				continue;
+			}
+
+			let body_start = match (line_number + 1..lines.len()).find(|l| !masked_lines[*l].trim().is_empty())
+			{
+				Some(start) => start,
+				None => continue,
+			};
+			let body_end = match expression_end(masked_lines, body_start)
+			{
+				Some(end) => end,
+				None => continue,
+			};
+
+			let target = self.indentation_width(&lines[line_number]) + unit;
+			let delta = target as isize - self.indentation_width(&lines[body_start]) as isize;
+			if delta == 0
+			{
+				continue;
+			}
+
+			if self.config.verbose
+			{
+				println!("Found incorrect arrow body indentation - {}", lines[body_start]);
+			}
+
+			for (current, line) in lines.iter_mut().enumerate().take(body_end + 1).skip(body_start)
+			{
+				if line.trim().is_empty() || source.protected.contains(current)
+				{
+					continue;
+				}
+				let width = (self.indentation_width(line) as isize + delta).max(0) as usize;
+				*line = self.reindent(line, width);
+			}
+			incorrect_arrow_indentations += 1;
+		}
+
+		let mut cleaned_content = String::from("");
+
+		for line in lines
+		{
+			cleaned_content.push_str(line.as_str());
+			cleaned_content.push_str("\n");
+		}
+
+		return (cleaned_content, incorrect_arrow_indentations);
+	}
+}
+
+/// Finds the line ending an expression, at a semicolon or comma outside of brackets or right before an unmatched closing bracket
+fn expression_end(masked_lines: &[String], start: usize) -> Option<usize>
+{
+	let mut depth = 0;
+	let mut last_code_line = start;
+
+	for (line_number, masked) in masked_lines.iter().enumerate().skip(start)
+	{
+		for b in masked.bytes()
+		{
+			match b
+			{
+				b'{' | b'(' | b'[' => depth += 1,
+				b'}' | b')' | b']' =>
+				{
+					depth -= 1;
+					if depth < 0
+					{
+						return Some(if masked.trim_start().starts_with(['}', ')', ']']) { last_code_line } else { line_number });
+					}
+				}
+				b';' | b',' if depth == 0 => return Some(line_number),
+				_ =>
+				{}
+			}
+		}
+		if !masked.trim().is_empty()
+		{
+			last_code_line = line_number;
+		}
+	}
+
+	return None;
+}
+
+/// Finds a function whose body is only `return ...;`, gives the line of the return statement and the closing curly brace
+fn single_return_body(lines: &[&str], masked_lines: &[String], line_number: usize) -> Option<(usize, usize)>
+{
+	let masked = masked_lines[line_number].trim_end();
+	if masked.len() != lines[line_number].trim_end().len()
+	{
+		return None;
+	}
+
+	let (header, mut next) = match masked.strip_suffix('{')
+	{
+		Some(header) => (header.trim_end(), line_number + 1),
+		None =>
+		{
+			let is_curly_next = masked_lines.get(line_number + 1).is_some_and(|m| m.trim() == "{") && lines[line_number + 1].trim() == "{";
+			if !is_curly_next
+			{
+				return None;
+			}
+			(masked, line_number + 2)
+		}
+	};
+
+	if !is_function_header(header)
+	{
+		return None;
+	}
+
+	while next < lines.len() && lines[next].trim().is_empty()
+	{
+		next += 1;
+	}
+	let return_line = next;
+	let return_code = masked_lines.get(return_line)?.trim();
+	let is_return = return_code.starts_with("return ") && return_code.len() == lines[return_line].trim().len() && return_code.ends_with(';') && return_code.matches(';').count() == 1;
+	if !is_return
+	{
+		return None;
+	}
+
+	next = return_line + 1;
+	while next < lines.len() && lines[next].trim().is_empty()
+	{
+		next += 1;
+	}
+	if lines.get(next)?.trim() != "}"
+	{
+		return None;
+	}
+
+	return Some((return_line, next));
+}
+
+/// Finds the `=>` of a single line function declaration like `int get length => items.length;`
+fn function_arrow(masked: &str) -> Option<usize>
+{
+	let code = masked.trim_end();
+	if !code.ends_with(';')
+	{
+		return None;
+	}
+
+	let mut depth = 0;
+	let mut arrow = None;
+	for (i, b) in code.bytes().enumerate()
+	{
+		match b
+		{
+			b'{' | b'(' | b'[' => depth += 1,
+			b'}' | b')' | b']' => depth -= 1,
+			b'=' if depth == 0 && code[i..].starts_with("=>") && arrow.is_none() => arrow = Some(i),
+			_ =>
+			{}
+		}
+	}
+
+	let arrow = arrow?;
+	if depth != 0 || !is_function_header(code[..arrow].trim_end())
+	{
+		return None;
+	}
+
+	return Some(arrow);
+}
+
+/// Whether code ending right before a function body declares a function, method, getter or setter
+fn is_function_header(header: &str) -> bool
+{
+	let trimmed = header.trim_start();
+	let first_word = trimmed.split(|c: char| !c.is_alphanumeric() && c != '_').next().unwrap_or("");
+	if trimmed.is_empty() || NON_FUNCTION_KEYWORDS.contains(&first_word) || trimmed.starts_with(['}', ')', '.', '('])
+	{
+		return false;
+	}
+
+	// Generators can't have arrow bodies
+	let signature = header.trim_end().strip_suffix("async").unwrap_or(header).trim_end();
+	if signature.ends_with('*')
+	{
+		return false;
+	}
+
+	let mut depth = 0;
+	for (i, b) in signature.bytes().enumerate()
+	{
+		match b
+		{
+			b'{' | b'(' | b'[' | b'<' => depth += 1,
+			b'}' | b')' | b']' | b'>' => depth -= 1,
+			// An assignment means this is a variable holding a closure
+			b'=' if depth == 0 && !signature[i..].starts_with("==") && !signature[..i].ends_with(['=', '!', '<', '>']) => return false,
+			_ =>
+			{}
+		}
+	}
+
+	if signature.ends_with(')')
+	{
+		return depth == 0;
+	}
+
+	let words: Vec<&str> = signature.split_whitespace().collect();
+	return words.len() >= 2 && words[words.len() - 2] == "get";
+}
+
+fn is_void_function(header: &str) -> bool
+{
+	let words: Vec<&str> = header.split(|c: char| !c.is_alphanumeric() && c != '_').filter(|w| !w.is_empty()).collect();
+	let is_setter = words.contains(&"set") && !words.contains(&"get");
+	let return_type = words.iter().find(|w| !["static", "external", "override"].contains(w));
+	return is_setter || return_type == Some(&"void");
+}
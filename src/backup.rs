@@ -0,0 +1,114 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::cache::content_hash;
+use crate::files::{find_project_root, write_atomically};
+
+/// Keeps the original content of every file changed by the last run, along with a journal listing them
+pub(crate) struct Journal
+{
+	/// Nothing is backed up when missing
+	folder: Option<PathBuf>,
+	entries: usize,
+}
+
+impl Journal
+{
+	pub(crate) fn disabled() -> Journal
+	{
+		return Journal { folder: None, entries: 0 };
+	}
+
+	/// Backs up into the project holding the path, the backup of the previous run is kept until a file gets changed
+	pub(crate) fn start(path: &Path) -> io::Result<Journal>
+	{
+		let folder = backup_folder(path).ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("No project found holding `{}`", path.display())))?;
+
+		return Ok(Journal { folder: Some(folder), entries: 0 });
+	}
+
+	/// Stores the original content of a file before it's replaced by the formatted one
+	pub(crate) fn record(&mut self, path: &Path, original: &str, formatted: &str) -> io::Result<()>
+	{
+		let folder = match &self.folder
+		{
+			Some(folder) => folder,
+			None => return Ok(()),
+		};
+
+		// A run changing nothing leaves the previous one to undo
+		if self.entries == 0
+		{
+			if folder.exists()
+			{
+				fs::remove_dir_all(folder)?;
+			}
+			fs::create_dir_all(folder)?;
+			fs::write(folder.join("journal"), "")?;
+		}
+
+		let backup_name = format!("{}.dart", self.entries);
+		fs::write(folder.join(&backup_name), original)?;
+
+		let mut journal = OpenOptions::new().append(true).open(folder.join("journal"))?;
+		writeln!(journal, "{}\t{:016x}\t{:016x}\t{}", backup_name, content_hash(original), content_hash(formatted), path.display())?;
+		journal.sync_all()?;
+
+		self.entries += 1;
+		return Ok(());
+	}
+}
+
+/// Puts back the original content of every file changed by the last run with a backup, unless any of them changed since
+pub(crate) fn undo(path: &Path, verbose: bool) -> io::Result<usize>
+{
+	let folder = backup_folder(path).filter(|folder| folder.join("journal").is_file()).ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No backup of an earlier run found"))?;
+
+	let mut entries: Vec<(PathBuf, PathBuf)> = Vec::new();
+	let mut changed: Vec<PathBuf> = Vec::new();
+
+	for line in fs::read_to_string(folder.join("journal"))?.lines()
+	{
+		let mut fields = line.splitn(4, '\t');
+		let (backup_name, original_hash, formatted_hash, file) = match (fields.next(), fields.next(), fields.next(), fields.next())
+		{
+			(Some(backup_name), Some(original_hash), Some(formatted_hash), Some(file)) => (backup_name, original_hash, formatted_hash, PathBuf::from(file)),
+			_ => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Broken backup journal line - {}", line))),
+		};
+
+		// A file still holding its original content wasn't written, like when writing it failed after the backup
+		let hash = fs::read_to_string(&file).map(|content| format!("{:016x}", content_hash(&content))).unwrap_or_default();
+		if hash == formatted_hash
+		{
+			entries.push((folder.join(backup_name), file));
+		}
+		else if hash != original_hash
+		{
+			changed.push(file);
+		}
+	}
+
+	if !changed.is_empty()
+	{
+		let files: Vec<String> = changed.iter().map(|file| format!("  {}", file.display())).collect();
+		return Err(io::Error::other(format!("Refusing to undo, files changed since they were formatted:\n{}", files.join("\n"))));
+	}
+
+	for (backup, file) in &entries
+	{
+		if verbose
+		{
+			println!("Restoring `{}`...", file.display());
+		}
+		write_atomically(file, &fs::read_to_string(backup)?)?;
+	}
+
+	fs::remove_dir_all(&folder)?;
+	return Ok(entries.len());
+}
+
+fn backup_folder(path: &Path) -> Option<PathBuf>
+{
+	return find_project_root(path).map(|root| root.join(".dart_tool").join("blink").join("backup"));
+}
@@ -0,0 +1,212 @@
+use crate::formatter::Formatter;
+use crate::source::Source;
+
+struct ContentLine
+{
+	line_number: usize,
+	blank_lines_before: usize,
+	opens_block: bool,
+	closes_block: bool,
+	is_lone_curly: bool,
+	member: Option<usize>,
+	spans_lines: bool,
+}
+
+struct Member
+{
+	container: usize,
+	code_lines: usize,
+}
+
+const CONTAINER_KEYWORDS: [&str; 4] = ["class", "mixin", "extension", "enum"];
+const DIRECTIVE_KEYWORDS: [&str; 4] = ["import", "export", "library", "part"];
+
+impl Formatter
+{
+	pub fn apply_blank_line_policy(&self, content: &String) -> String
+	{
+		let source = Source::new(content);
+		let lines = &source.lines;
+
+		let content_lines = self.content_lines(&source);
+		let last_directive = last_directive_line(&source.masked_lines);
+
+		let mut cleaned_content = String::from("");
+
+		// Blank lines at the very start of the file are left alone
+		for _ in 0..content_lines.first().map(|c| c.line_number).unwrap_or(0)
+		{
+			cleaned_content.push_str("\n");
+		}
+
+		for (i, current) in content_lines.iter().enumerate()
+		{
+			if i > 0
+			{
+				let previous = &content_lines[i - 1];
+				let blank_lines = self.blank_lines_between(previous, current, last_directive);
+				for _ in 0..blank_lines
+				{
+					cleaned_content.push_str("\n");
+				}
+			}
+
+			cleaned_content.push_str(lines[current.line_number]);
+			cleaned_content.push_str("\n");
+		}
+
+		return cleaned_content;
+	}
+
+	fn blank_lines_between(&self, previous: &ContentLine, current: &ContentLine, last_directive: Option<usize>) -> usize
+	{
+		let mut blank_lines = current.blank_lines_before;
+
+		if previous.opens_block && !self.config.blank_lines.after_curly
+		{
+			return 0;
+		}
+		if current.closes_block && !self.config.blank_lines.before_curly
+		{
+			return 0;
+		}
+		if current.is_lone_curly
+		{
+			return 0;
+		}
+
+		// Directives aren't members, the blank line after them is only up to blank_line_after_imports
+		if last_directive == Some(previous.line_number)
+		{
+			if self.config.blank_lines.after_imports
+			{
+				blank_lines = blank_lines.max(1);
+			}
+			return blank_lines.min(self.config.blank_lines.max_consecutive);
+		}
+
+		// Only members spanning several lines get forced apart, runs of single line fields stay together
+		let is_member_boundary = previous.member.is_some() && current.member.is_some() && previous.member != current.member;
+		if self.config.blank_lines.between_members && is_member_boundary && (previous.spans_lines || current.spans_lines)
+		{
+			blank_lines = 1;
+		}
+
+		return blank_lines.min(self.config.blank_lines.max_consecutive);
+	}
+
+	fn content_lines(&self, source: &Source) -> Vec<ContentLine>
+	{
+		let mut content_lines: Vec<ContentLine> = Vec::new();
+		let mut blank_lines_before = 0;
+
+		// Every open curly brace, and whether it holds class members
+		let mut containers: Vec<bool> = vec![true];
+		let mut members: Vec<Member> = Vec::new();
+		let mut open_members: Vec<usize> = Vec::new();
+		let mut header = String::from("");
+
+		for (line_number, line) in source.lines.iter().enumerate()
+		{
+			let is_forbidden = source.protected.contains(line_number);
+			if line.trim().is_empty() && !is_forbidden
+			{
+				blank_lines_before += 1;
+				continue;
+			}
+
+			let masked = source.masked_lines[line_number].trim();
+			let depth = containers.len();
+			let is_member_level = containers.last() == Some(&true);
+			let is_code = !masked.is_empty() && !masked.starts_with('@');
+
+			let has_open_member = open_members.last().is_some_and(|index| members[*index].container == depth);
+			if is_member_level && !has_open_member && !masked.starts_with('}')
+			{
+				members.push(Member { container: depth, code_lines: 0 });
+				open_members.push(members.len() - 1);
+			}
+
+			let member = open_members.last().copied();
+			if let Some(index) = member
+			{
+				if is_code
+				{
+					members[index].code_lines += 1;
+				}
+			}
+
+			for b in masked.bytes()
+			{
+				match b
+				{
+					b'{' =>
+					{
+						let is_container = header.split(|c: char| !c.is_alphanumeric() && c != '_').any(|w| CONTAINER_KEYWORDS.contains(&w));
+						containers.push(is_container);
+						header.clear();
+					}
+					b'}' =>
+					{
+						if containers.len() > 1
+						{
+							containers.pop();
+						}
+						header.clear();
+					}
+					b';' => header.clear(),
+					_ => header.push(b as char),
+				}
+			}
+			header.push(' ');
+
+			while let Some(index) = open_members.last()
+			{
+				let container = members[*index].container;
+				let is_finished = container == containers.len() && (masked.ends_with(';') || masked.ends_with('}'));
+				if container > containers.len() || is_finished
+				{
+					open_members.pop();
+					continue;
+				}
+				break;
+			}
+
+			content_lines.push(ContentLine { line_number, blank_lines_before, opens_block: masked.ends_with('{'), closes_block: masked.starts_with('}'), is_lone_curly: masked == "{", member, spans_lines: false });
+			blank_lines_before = 0;
+		}
+
+		for content_line in &mut content_lines
+		{
+			content_line.spans_lines = content_line.member.is_some_and(|index| members[index].code_lines > 1);
+		}
+
+		return content_lines;
+	}
+}
+
+fn last_directive_line(masked_lines: &[String]) -> Option<usize>
+{
+	let mut last_directive = None;
+	let mut in_directive = false;
+
+	for (line_number, masked) in masked_lines.iter().enumerate()
+	{
+		let trimmed = masked.trim();
+		if trimmed.is_empty()
+		{
+			continue;
+		}
+
+		let starts_directive = DIRECTIVE_KEYWORDS.iter().any(|k| trimmed.starts_with(k) && trimmed[k.len()..].starts_with(|c: char| c.is_whitespace() || c == '_'));
+		if !starts_directive && !in_directive
+		{
+			break;
+		}
+
+		in_directive = !trimmed.ends_with(';');
+		last_directive = Some(line_number);
+	}
+
+	return last_directive;
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::files::find_project_root;
+
+/// Remembers which files are already formatted, by the hash of their content and config, so unchanged files are not formatted again.
+/// Each project has its own cache file, read the first time one of its files is looked at and written once at the end of the run.
+pub(crate) struct Cache
+{
+	is_enabled: bool,
+	/// Dry runs check against the cache but don't change it
+	is_read_only: bool,
+	/// Cache file of the project holding each folder looked at so far, if any
+	folders: HashMap<PathBuf, Option<PathBuf>>,
+	projects: HashMap<PathBuf, ProjectCache>,
+}
+
+#[derive(Default)]
+struct ProjectCache
+{
+	entries: HashMap<PathBuf, u64>,
+	is_changed: bool,
+}
+
+impl Cache
+{
+	pub(crate) fn disabled() -> Cache
+	{
+		return Cache { is_enabled: false, is_read_only: true, folders: HashMap::new(), projects: HashMap::new() };
+	}
+
+	pub(crate) fn new(is_read_only: bool) -> Cache
+	{
+		return Cache { is_enabled: true, is_read_only, folders: HashMap::new(), projects: HashMap::new() };
+	}
+
+	pub(crate) fn is_formatted(&mut self, path: &Path, config: &Config, content: &str) -> bool
+	{
+		let hash = entry_hash(config, content);
+		return self.project(path).is_some_and(|project| project.entries.get(path) == Some(&hash));
+	}
+
+	pub(crate) fn mark_formatted(&mut self, path: &Path, config: &Config, content: &str)
+	{
+		let hash = entry_hash(config, content);
+		if let Some(project) = self.project(path)
+		{
+			if project.entries.get(path) != Some(&hash)
+			{
+				project.entries.insert(path.to_path_buf(), hash);
+				project.is_changed = true;
+			}
+		}
+	}
+
+	pub(crate) fn save(&self)
+	{
+		if self.is_read_only
+		{
+			return;
+		}
+
+		for (file, project) in self.projects.iter().filter(|(_, project)| project.is_changed)
+		{
+			let mut content = format!("{}\n", env!("CARGO_PKG_VERSION"));
+			for (path, hash) in &project.entries
+			{
+				content.push_str(&format!("{:016x}\t{}\n", hash, path.display()));
+			}
+
+			let res = file.parent().map_or(Ok(()), fs::create_dir_all).and_then(|_| fs::write(file, content));
+			if let Err(error) = res
+			{
+				eprintln!("Failed to write formatting cache `{}` - {}", file.display(), error);
+			}
+		}
+	}
+
+	fn project(&mut self, path: &Path) -> Option<&mut ProjectCache>
+	{
+		if !self.is_enabled
+		{
+			return None;
+		}
+
+		let folder = path.parent().unwrap_or(path).to_path_buf();
+		let file = self.folders.entry(folder).or_insert_with_key(|folder| find_project_root(folder).map(|root| root.join(".dart_tool").join("blink").join("cache"))).clone()?;
+
+		return Some(self.projects.entry(file).or_insert_with_key(|file| load_project(file)));
+	}
+}
+
+/// Starts over when the cache was written by another version of blink
+fn load_project(file: &Path) -> ProjectCache
+{
+	let mut project = ProjectCache::default();
+
+	if let Ok(content) = fs::read_to_string(file)
+	{
+		let mut lines = content.lines();
+		if lines.next() == Some(env!("CARGO_PKG_VERSION"))
+		{
+			for line in lines
+			{
+				if let Some((hash, path)) = line.split_once('\t')
+				{
+					if let Ok(hash) = u64::from_str_radix(hash, 16)
+					{
+						project.entries.insert(PathBuf::from(path), hash);
+					}
+				}
+			}
+		}
+	}
+
+	return project;
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// 64-bit FNV-1a hash, which unlike the hasher of the standard library stays the same across Rust versions, so hashes written to disk remain valid
+pub(crate) fn content_hash(content: &str) -> u64
+{
+	return fnv1a(FNV_OFFSET_BASIS, content.as_bytes());
+}
+
+/// Hash of the content along with the settings of the config it was formatted with
+fn entry_hash(config: &Config, content: &str) -> u64
+{
+	let hash = fnv1a(FNV_OFFSET_BASIS, config.formatting_key().as_bytes());
+	return fnv1a(fnv1a(hash, &[0]), content.as_bytes());
+}
+
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64
+{
+	let mut hash = hash;
+	for b in bytes
+	{
+		hash ^= *b as u64;
+		hash = hash.wrapping_mul(FNV_PRIME);
+	}
+	return hash;
+}
@@ -0,0 +1,222 @@
+use crate::formatter::Formatter;
+use crate::scanner::is_identifier_byte;
+use crate::source::Source;
+
+impl Formatter
+{
+	/// Puts every call of a method chain or cascade on its own line, once the chain gets too long or already spans several lines
+	pub fn format_method_chains(&self, content: &String) -> (String, i32)
+	{
+		let max_length = match self.config.max_chain_length
+		{
+			Some(max_length) => max_length,
+			None => return (content.to_string(), 0),
+		};
+
+		let source = Source::new(content);
+		let masked_lines = &source.masked_lines;
+
+		let unit = self.config.indentation.size.max(1);
+		let mut incorrect_chains = 0;
+		let mut head_width = 0;
+		let mut cleaned_content = String::from("");
+
+		for (line_number, line) in source.lines.iter().enumerate()
+		{
+			let masked = masked_lines[line_number].as_str();
+			let is_forbidden = source.protected.contains(line_number);
+			let is_continuation = starts_with_chain_segment(masked.trim_start());
+
+			if !is_continuation
+			{
+				head_width = self.indentation_width(line);
+			}
+
+			if is_forbidden || masked.trim().is_empty() || masked.trim_start().starts_with([')', ']', '}'])
+			{
+				cleaned_content.push_str(line);
+				cleaned_content.push_str("\n");
+				continue;
+			}
+
+			let delta = line.len() - line.trim_start().len();
+			let segments = chain_segments(&masked[delta..]);
+
+			let continues_on_next_line = masked_lines[line_number + 1..].iter().find(|m| !m.trim().is_empty()).is_some_and(|m| starts_with_chain_segment(m.trim_start()));
+			let length = self.indentation_width(line) + line.trim_start().chars().count();
+			let is_too_long = length > max_length && segments.len() >= 2;
+
+			// A continuation line starts with a segment, that one is already on its own line
+			let splits: Vec<usize> = segments.into_iter().filter(|s| *s > 0).collect();
+			let should_split = !splits.is_empty() && (is_too_long || continues_on_next_line || is_continuation);
+
+			let target = head_width + unit;
+			let is_misindented = is_continuation && self.indentation_width(line) != target;
+
+			if !should_split && !is_misindented
+			{
+				cleaned_content.push_str(line);
+				cleaned_content.push_str("\n");
+				continue;
+			}
+
+			if self.config.verbose
+			{
+				println!("Found incorrect method chain - {}", line);
+			}
+
+			let body = &line[delta..];
+			let mut pieces: Vec<&str> = Vec::new();
+			let mut last = 0;
+			if should_split
+			{
+				for split in splits
+				{
+					pieces.push(body[last..split].trim_end());
+					last = split;
+				}
+			}
+			pieces.push(&body[last..]);
+
+			for (k, piece) in pieces.iter().enumerate()
+			{
+				if k == 0 && !is_continuation
+				{
+					cleaned_content.push_str(&line[..delta]);
+					cleaned_content.push_str(piece);
+				}
+				else
+				{
+					cleaned_content.push_str(self.reindent(piece, target).as_str());
+				}
+				cleaned_content.push_str("\n");
+			}
+
+			incorrect_chains += 1;
+		}
+
+		return (cleaned_content, incorrect_chains);
+	}
+}
+
+fn starts_with_chain_segment(masked: &str) -> bool
+{
+	return masked.starts_with("..") && !masked.starts_with("...") || masked.starts_with("?.") || (masked.starts_with('.') && masked[1..].starts_with(|c: char| c.is_alphabetic() || c == '_' || c == '$'));
+}
+
+/// Finds where the calls and cascades of a chain start, only counting the ones outside of any brackets
+fn chain_segments(masked: &str) -> Vec<usize>
+{
+	let bytes = masked.as_bytes();
+	let mut segments: Vec<usize> = Vec::new();
+	let mut depth = 0;
+	let mut in_cascade = false;
+	let mut i = 0;
+
+	while i < bytes.len()
+	{
+		match bytes[i]
+		{
+			b'(' | b'[' | b'{' => depth += 1,
+			b')' | b']' | b'}' => depth -= 1,
+			b'.' | b'?' if depth == 0 =>
+			{
+				let start = i;
+				let operator = ["?..", "..", "?.", "."].iter().find(|o| masked[i..].starts_with(*o)).copied();
+
+				if let Some(operator) = operator
+				{
+					let name_start = i + operator.len();
+					let is_spread = masked[i..].starts_with("...");
+					let is_cascade = operator.ends_with("..");
+
+					if !is_spread && bytes.get(name_start).is_some_and(|b| b.is_ascii_alphabetic() || *b == b'_' || *b == b'$') && (is_cascade || !is_number_before(bytes, start))
+					{
+						let mut name_end = name_start;
+						while name_end < bytes.len() && is_identifier_byte(bytes[name_end])
+						{
+							name_end += 1;
+						}
+
+						let is_call = masked[name_end..].trim_start().starts_with(['(', '<']);
+						let is_static_access = segments.is_empty() && !is_cascade && is_type_name_before(masked, start);
+
+						// Past a cascade, the calls and assignments of its sections stay with them
+						if (is_cascade || (is_call && !in_cascade)) && !is_static_access
+						{
+							segments.push(start);
+							in_cascade |= is_cascade;
+						}
+						i = skip_type_arguments(bytes, name_end);
+						continue;
+					}
+					i += operator.len();
+					continue;
+				}
+				// A ternary or `??` starts another operand
+				if !in_cascade
+				{
+					segments.clear();
+				}
+			}
+			// Calls after an operator, assignment or comma belong to another receiver, only the last one's chain is split
+			b'+' | b'-' | b'*' | b'/' | b'%' | b'<' | b'>' | b'=' | b'&' | b'|' | b'^' | b',' | b':' if depth == 0 && !in_cascade => segments.clear(),
+			_ =>
+			{}
+		}
+		i += 1;
+	}
+
+	return segments;
+}
+
+/// Skips the type arguments of a generic call like `map<int>(...)`, so their angle brackets aren't taken for operators
+fn skip_type_arguments(bytes: &[u8], position: usize) -> usize
+{
+	if bytes.get(position) != Some(&b'<')
+	{
+		return position;
+	}
+
+	let mut depth = 0;
+	for (i, b) in bytes.iter().enumerate().skip(position)
+	{
+		match b
+		{
+			b'<' => depth += 1,
+			b'>' =>
+			{
+				depth -= 1;
+				if depth == 0
+				{
+					return i + 1;
+				}
+			}
+			b'(' | b';' => return position,
+			_ =>
+			{}
+		}
+	}
+
+	return position;
+}
+
+fn is_number_before(bytes: &[u8], position: usize) -> bool
+{
+	let mut start = position;
+	while start > 0 && is_identifier_byte(bytes[start - 1])
+	{
+		start -= 1;
+	}
+	return start < position && bytes[start].is_ascii_digit();
+}
+
+/// Calls on classes and import prefixes like `Navigator.of(context)` stay on the line of the class, as do `this` and `super`
+fn is_type_name_before(masked: &str, position: usize) -> bool
+{
+	let before = &masked[..position];
+	let name_start = before.rfind(|c: char| !c.is_alphanumeric() && c != '_' && c != '$').map(|i| i + 1).unwrap_or(0);
+	let name = &before[name_start..];
+
+	return name.starts_with(|c: char| c.is_uppercase()) || name == "this" || name == "super";
+}
@@ -1,8 +1,12 @@
-use ec4rs::property::IndentStyle;
-use std::{fmt, path::Path};
+use ec4rs::property::{EndOfLine, FinalNewline, IndentStyle, TrimTrailingWs};
+use std::{
+	collections::HashMap,
+	fmt,
+	path::{Path, PathBuf},
+};
 
-pub(crate) fn load(verbose: bool, dryrun: bool, path: &Path) -> Config
-// pub(crate) fn load(verbose: bool, dryrun: bool, use_treesitter_to_format: bool, path: &Path) -> Config
+pub fn load(verbose: bool, dryrun: bool, path: &Path) -> Config
+// pub fn load(verbose: bool, dryrun: bool, use_treesitter_to_format: bool, path: &Path) -> Config
 {
 	fn load_properties_at_file_path(file_path: &Path) -> Option<ec4rs::Properties>
 	{
@@ -15,7 +19,7 @@ pub(crate) fn load(verbose: bool, dryrun: bool, path: &Path) -> Config
 			}
 			Err(error) =>
 			{
-				println!("Warning: Unable to load editorconfig - Resorting to defaults\nReason: {}", error);
+				eprintln!("Warning: Unable to load editorconfig - Resorting to defaults\nReason: {}", error);
 				return None;
 			}
 		}
@@ -43,7 +47,26 @@ pub(crate) fn load(verbose: bool, dryrun: bool, path: &Path) -> Config
 		}
 	}
 
-	let default_config = Config { verbose, dryrun, indentation: Indentation { style: IndentationStyle::Tabs, size: 2 }, curly_brace_on_next_line: true, prefer_double_quotes: true };
+	let default_config = Config {
+		verbose,
+		dryrun,
+		indentation: Indentation { style: IndentationStyle::Tabs, size: 2 },
+		curly_brace_on_next_line: true,
+		closure_curly_brace_on_next_line: false,
+		collection_curly_brace_on_next_line: false,
+		prefer_double_quotes: true,
+		trailing_commas: TrailingCommas::Preserve,
+		control_flow_braces: ControlFlowBraces::Never,
+		function_bodies: FunctionBodies::Preserve,
+		flutter_widget_trees: false,
+		max_chain_length: None,
+		organize_imports: true,
+		normalize_spacing: true,
+		trim_trailing_whitespace: true,
+		insert_final_newline: true,
+		end_of_line: None,
+		blank_lines: BlankLines { max_consecutive: 1, after_curly: false, before_curly: false, between_members: false, after_imports: true },
+	};
 	// let default_config = Config { verbose, dryrun, use_treesitter_to_format, indentation: Indentation { style: IndentationStyle::Tabs, size: 2 }, curly_brace_on_next_line: true, prefer_double_quotes: true };
 
 	let res = load_properties(path);
@@ -55,9 +78,51 @@ pub(crate) fn load(verbose: bool, dryrun: bool, path: &Path) -> Config
 			let indent_style: IndentStyle = cfg.get::<IndentStyle>().unwrap_or(IndentStyle::Tabs);
 			let indent_size = cfg.get_raw_for_key("indent_size").into_str().parse::<usize>().unwrap_or(default_config.indentation.size);
 			let curly_brace_on_next_line = cfg.get_raw_for_key("curly_brace_on_next_line").into_str().parse::<bool>().unwrap_or(default_config.curly_brace_on_next_line);
+			let closure_curly_brace_on_next_line = cfg.get_raw_for_key("closure_curly_brace_on_next_line").into_str().parse::<bool>().unwrap_or(default_config.closure_curly_brace_on_next_line);
+			let collection_curly_brace_on_next_line = cfg.get_raw_for_key("collection_curly_brace_on_next_line").into_str().parse::<bool>().unwrap_or(default_config.collection_curly_brace_on_next_line);
 			let prefer_double_quotes = cfg.get_raw_for_key("prefer_double_quotes").into_str().parse::<bool>().unwrap_or(default_config.prefer_double_quotes);
+			let flutter_widget_trees = cfg.get_raw_for_key("flutter_widget_trees").into_str().parse::<bool>().unwrap_or(default_config.flutter_widget_trees);
+			// Widget trees read best with trailing commas, so that is what the flutter profile defaults to
+			let default_trailing_commas = if flutter_widget_trees { TrailingCommas::Multiline } else { default_config.trailing_commas };
+			let trailing_commas = load_trailing_commas(cfg.get_raw_for_key("trailing_commas").into_str()).unwrap_or(default_trailing_commas);
+			let control_flow_braces = load_control_flow_braces(cfg.get_raw_for_key("curly_braces_for_control_flow").into_str()).unwrap_or(default_config.control_flow_braces);
+			let function_bodies = load_function_bodies(cfg.get_raw_for_key("function_bodies").into_str()).unwrap_or(default_config.function_bodies);
+			let max_chain_length = cfg.get_raw_for_key("max_chain_length").into_str().parse::<usize>().ok().or(default_config.max_chain_length);
+			let organize_imports = cfg.get_raw_for_key("organize_imports").into_str().parse::<bool>().unwrap_or(default_config.organize_imports);
+			let normalize_spacing = cfg.get_raw_for_key("normalize_spacing").into_str().parse::<bool>().unwrap_or(default_config.normalize_spacing);
+			let trim_trailing_whitespace = match cfg.get::<TrimTrailingWs>()
+			{
+				Ok(TrimTrailingWs::Value(trim)) => trim,
+				Err(_) => default_config.trim_trailing_whitespace,
+			};
+			let insert_final_newline = match cfg.get::<FinalNewline>()
+			{
+				Ok(FinalNewline::Value(insert)) => insert,
+				Err(_) => default_config.insert_final_newline,
+			};
+			let end_of_line = cfg.get::<EndOfLine>().ok().map(load_line_ending);
+			let blank_lines = load_blank_lines(&cfg, default_config.blank_lines);
 
-			return Config { verbose, dryrun, indentation: load_indentation(indent_style, indent_size), curly_brace_on_next_line, prefer_double_quotes };
+			return Config {
+				verbose,
+				dryrun,
+				indentation: load_indentation(indent_style, indent_size),
+				curly_brace_on_next_line,
+				closure_curly_brace_on_next_line,
+				collection_curly_brace_on_next_line,
+				prefer_double_quotes,
+				trailing_commas,
+				control_flow_braces,
+				function_bodies,
+				flutter_widget_trees,
+				max_chain_length,
+				organize_imports,
+				normalize_spacing,
+				trim_trailing_whitespace,
+				insert_final_newline,
+				end_of_line,
+				blank_lines,
+			};
 			// return Config { verbose, dryrun, use_treesitter_to_format, indentation: load_indentation(indent_style, indent_size), curly_brace_on_next_line, prefer_double_quotes };
 		}
 
@@ -83,31 +148,174 @@ fn load_indentation(style: IndentStyle, size: usize) -> Indentation
 	}
 }
 
+fn load_blank_lines(cfg: &ec4rs::Properties, defaults: BlankLines) -> BlankLines
+{
+	let load_bool = |key: &str, default: bool| cfg.get_raw_for_key(key).into_str().parse::<bool>().unwrap_or(default);
+
+	let max_consecutive = cfg.get_raw_for_key("max_blank_lines").into_str().parse::<usize>().unwrap_or(defaults.max_consecutive);
+	let after_curly = load_bool("blank_lines_after_curly", defaults.after_curly);
+	let before_curly = load_bool("blank_lines_before_curly", defaults.before_curly);
+	let between_members = load_bool("blank_line_between_members", defaults.between_members);
+	let after_imports = load_bool("blank_line_after_imports", defaults.after_imports);
+
+	return BlankLines { max_consecutive, after_curly, before_curly, between_members, after_imports };
+}
+
+fn load_line_ending(end_of_line: EndOfLine) -> LineEnding
+{
+	match end_of_line
+	{
+		EndOfLine::Lf => LineEnding::Lf,
+		EndOfLine::CrLf => LineEnding::CrLf,
+		EndOfLine::Cr => LineEnding::Cr,
+	}
+}
+
+fn load_trailing_commas(value: &str) -> Option<TrailingCommas>
+{
+	match value.to_lowercase().as_str()
+	{
+		"preserve" => Some(TrailingCommas::Preserve),
+		"multiline" => Some(TrailingCommas::Multiline),
+		"multiline_only" => Some(TrailingCommas::MultilineOnly),
+		_ => None,
+	}
+}
+
+fn load_control_flow_braces(value: &str) -> Option<ControlFlowBraces>
+{
+	match value.to_lowercase().as_str()
+	{
+		"never" => Some(ControlFlowBraces::Never),
+		"multiline" => Some(ControlFlowBraces::Multiline),
+		"always" => Some(ControlFlowBraces::Always),
+		_ => None,
+	}
+}
+
+fn load_function_bodies(value: &str) -> Option<FunctionBodies>
+{
+	match value.to_lowercase().as_str()
+	{
+		"preserve" => Some(FunctionBodies::Preserve),
+		"arrow" => Some(FunctionBodies::Arrow),
+		"block" => Some(FunctionBodies::Block),
+		_ => None,
+	}
+}
+
 #[derive(Debug, Copy, Clone)]
-pub(crate) struct Config
+pub struct Config
 {
-	pub(crate) verbose: bool,
-	pub(crate) dryrun: bool,
-	// pub(crate) use_treesitter_to_format: bool,
-	pub(crate) indentation: Indentation,
-	pub(crate) curly_brace_on_next_line: bool,
-	pub(crate) prefer_double_quotes: bool,
+	pub verbose: bool,
+	pub dryrun: bool,
+	// pub use_treesitter_to_format: bool,
+	pub indentation: Indentation,
+	pub curly_brace_on_next_line: bool,
+	/// Move the curly braces of closures like `setState(() {` too, only applies with curly_brace_on_next_line
+	pub closure_curly_brace_on_next_line: bool,
+	/// Move the curly braces of map and set literals too, only applies with curly_brace_on_next_line
+	pub collection_curly_brace_on_next_line: bool,
+	pub prefer_double_quotes: bool,
+	pub trailing_commas: TrailingCommas,
+	pub control_flow_braces: ControlFlowBraces,
+	pub function_bodies: FunctionBodies,
+	/// Indent by parentheses and square brackets too, like Flutter widget trees want
+	pub flutter_widget_trees: bool,
+	/// Line length past which method chains and cascades get a line per call, chains are left alone if omitted
+	pub max_chain_length: Option<usize>,
+	pub organize_imports: bool,
+	pub normalize_spacing: bool,
+	pub trim_trailing_whitespace: bool,
+	pub insert_final_newline: bool,
+	/// Line endings to convert to, the ones found in the file are kept if omitted
+	pub end_of_line: Option<LineEnding>,
+	pub blank_lines: BlankLines,
 }
 
 #[derive(Debug, Copy, Clone)]
-pub(crate) struct Indentation
+pub struct BlankLines
 {
-	pub(crate) style: IndentationStyle,
-	pub(crate) size: usize,
+	/// Most blank lines allowed in a row
+	pub max_consecutive: usize,
+	/// Keep blank lines directly after an opening curly brace
+	pub after_curly: bool,
+	/// Keep blank lines directly before a closing curly brace
+	pub before_curly: bool,
+	/// Put a single blank line between multi-line class members and top level declarations
+	pub between_members: bool,
+	/// Put a blank line after the import block
+	pub after_imports: bool,
 }
 
 #[derive(Debug, Copy, Clone)]
-pub(crate) enum IndentationStyle
+pub struct Indentation
+{
+	pub style: IndentationStyle,
+	pub size: usize,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum IndentationStyle
 {
 	Tabs,
 	Spaces,
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LineEnding
+{
+	Lf,
+	CrLf,
+	Cr,
+}
+
+impl LineEnding
+{
+	pub fn as_str(&self) -> &'static str
+	{
+		match self
+		{
+			LineEnding::Lf => "\n",
+			LineEnding::CrLf => "\r\n",
+			LineEnding::Cr => "\r",
+		}
+	}
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TrailingCommas
+{
+	/// Leave trailing commas alone
+	Preserve,
+	/// Add trailing commas to multi-line lists
+	Multiline,
+	/// Add trailing commas to multi-line lists and remove them from single-line ones
+	MultilineOnly,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ControlFlowBraces
+{
+	/// Leave braceless control flow bodies alone
+	Never,
+	/// Add curly braces to bodies that don't share the line of their header
+	Multiline,
+	/// Add curly braces to every braceless body
+	Always,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FunctionBodies
+{
+	/// Leave function bodies alone
+	Preserve,
+	/// Turn bodies holding only a return statement into `=>` bodies
+	Arrow,
+	/// Turn single line `=>` bodies into blocks
+	Block,
+}
+
 impl std::fmt::Display for IndentationStyle
 {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
@@ -118,19 +326,151 @@ impl std::fmt::Display for IndentationStyle
 
 impl Config
 {
-	pub(crate) fn display(&self) -> String
+	/// Every setting changing the formatting written out like in editorconfig, it doesn't depend on how Rust prints or hashes the config
+	pub fn formatting_key(&self) -> String
+	{
+		let indent_style = match self.indentation.style
+		{
+			IndentationStyle::Tabs => "tab",
+			IndentationStyle::Spaces => "space",
+		};
+		let trailing_commas = match self.trailing_commas
+		{
+			TrailingCommas::Preserve => "preserve",
+			TrailingCommas::Multiline => "multiline",
+			TrailingCommas::MultilineOnly => "multiline_only",
+		};
+		let control_flow_braces = match self.control_flow_braces
+		{
+			ControlFlowBraces::Never => "never",
+			ControlFlowBraces::Multiline => "multiline",
+			ControlFlowBraces::Always => "always",
+		};
+		let function_bodies = match self.function_bodies
+		{
+			FunctionBodies::Preserve => "preserve",
+			FunctionBodies::Arrow => "arrow",
+			FunctionBodies::Block => "block",
+		};
+		let end_of_line = match self.end_of_line
+		{
+			None => "unset",
+			Some(LineEnding::Lf) => "lf",
+			Some(LineEnding::CrLf) => "crlf",
+			Some(LineEnding::Cr) => "cr",
+		};
+		let max_chain_length = self.max_chain_length.map_or(String::from("unset"), |length| length.to_string());
+
+		let settings = [
+			("indent_style", indent_style.to_string()),
+			("indent_size", self.indentation.size.to_string()),
+			("curly_brace_on_next_line", self.curly_brace_on_next_line.to_string()),
+			("closure_curly_brace_on_next_line", self.closure_curly_brace_on_next_line.to_string()),
+			("collection_curly_brace_on_next_line", self.collection_curly_brace_on_next_line.to_string()),
+			("prefer_double_quotes", self.prefer_double_quotes.to_string()),
+			("trailing_commas", trailing_commas.to_string()),
+			("curly_braces_for_control_flow", control_flow_braces.to_string()),
+			("function_bodies", function_bodies.to_string()),
+			("flutter_widget_trees", self.flutter_widget_trees.to_string()),
+			("max_chain_length", max_chain_length),
+			("organize_imports", self.organize_imports.to_string()),
+			("normalize_spacing", self.normalize_spacing.to_string()),
+			("trim_trailing_whitespace", self.trim_trailing_whitespace.to_string()),
+			("insert_final_newline", self.insert_final_newline.to_string()),
+			("end_of_line", end_of_line.to_string()),
+			("max_blank_lines", self.blank_lines.max_consecutive.to_string()),
+			("blank_lines_after_curly", self.blank_lines.after_curly.to_string()),
+			("blank_lines_before_curly", self.blank_lines.before_curly.to_string()),
+			("blank_line_between_members", self.blank_lines.between_members.to_string()),
+			("blank_line_after_imports", self.blank_lines.after_imports.to_string()),
+		];
+
+		return settings.iter().map(|(key, value)| format!("{} = {}\n", key, value)).collect();
+	}
+
+	pub fn display(&self) -> String
 	{
 		let mut s = String::from("");
 
 		let curlies = format!("Curlies on next line: {}\n", self.curly_brace_on_next_line);
 		s.push_str(curlies.as_str());
 
+		let closure_curlies = format!("Closure curlies on next line: {}\n", self.closure_curly_brace_on_next_line);
+		s.push_str(closure_curlies.as_str());
+
+		let collection_curlies = format!("Collection curlies on next line: {}\n", self.collection_curly_brace_on_next_line);
+		s.push_str(collection_curlies.as_str());
+
 		let double_quotes = format!("Prefer double quotes: {}\n", self.prefer_double_quotes);
 		s.push_str(double_quotes.as_str());
 
+		let trailing_commas = format!("Trailing commas: {:?}\n", self.trailing_commas);
+		s.push_str(trailing_commas.as_str());
+
+		let control_flow_braces = format!("Control flow curlies: {:?}\n", self.control_flow_braces);
+		s.push_str(control_flow_braces.as_str());
+
+		let function_bodies = format!("Function bodies: {:?}\n", self.function_bodies);
+		s.push_str(function_bodies.as_str());
+
+		let flutter = format!("Flutter widget trees: {}\n", self.flutter_widget_trees);
+		s.push_str(flutter.as_str());
+
+		let max_chain_length = match self.max_chain_length
+		{
+			Some(length) => format!("Max chain length: {}\n", length),
+			None => String::from("Max chain length: Preserve\n"),
+		};
+		s.push_str(max_chain_length.as_str());
+
+		let imports = format!("Organize imports: {}\n", self.organize_imports);
+		s.push_str(imports.as_str());
+
+		let spacing = format!("Normalize spacing: {}\n", self.normalize_spacing);
+		s.push_str(spacing.as_str());
+
+		let trim = format!("Trim trailing whitespace: {}\n", self.trim_trailing_whitespace);
+		s.push_str(trim.as_str());
+
+		let final_newline = format!("Insert final newline: {}\n", self.insert_final_newline);
+		s.push_str(final_newline.as_str());
+
+		let end_of_line = match self.end_of_line
+		{
+			Some(ending) => format!("End of line: {:?}\n", ending),
+			None => String::from("End of line: Preserve\n"),
+		};
+		s.push_str(end_of_line.as_str());
+
+		let blank_lines = format!("Blank lines: {:?}\n", self.blank_lines);
+		s.push_str(blank_lines.as_str());
+
 		let indentation = format!("Indentation: {} - {}", self.indentation.style, self.indentation.size);
 		s.push_str(indentation.as_str());
 
 		return s;
 	}
 }
+
+/// Configs loaded so far by folder, the `.editorconfig` files of a folder apply to every file in it
+pub struct Configs
+{
+	verbose: bool,
+	dryrun: bool,
+	folders: HashMap<PathBuf, Config>,
+}
+
+impl Configs
+{
+	pub fn new(verbose: bool, dryrun: bool) -> Configs
+	{
+		return Configs { verbose, dryrun, folders: HashMap::new() };
+	}
+
+	/// The config for a file, or for the files of a folder
+	pub fn get(&mut self, path: &Path) -> Config
+	{
+		let folder = if path.is_dir() { path } else { path.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new(".")) };
+		return *self.folders.entry(folder.to_path_buf()).or_insert_with_key(|folder| load(self.verbose, self.dryrun, folder));
+	}
+}
@@ -0,0 +1,396 @@
+use crate::config::ControlFlowBraces;
+use crate::formatter::Formatter;
+use crate::scanner::{is_identifier_byte, starts_with_word};
+use crate::source::Source;
+
+struct ControlHeader
+{
+	keyword: &'static str,
+	/// Byte offset right after the condition, or after `else`/`do` when there is none
+	end: usize,
+}
+
+/// Changes to the lines of a file, applied once every control flow statement has been looked at so line numbers and offsets stay put
+struct BraceEdits
+{
+	lines: Vec<String>,
+	/// Closing curly braces to put after each line, innermost last
+	closers: Vec<Vec<String>>,
+	/// Width to add to the indentation of each line, for bodies that were written at the level of their header
+	extra_indentation: Vec<usize>,
+}
+
+impl Formatter
+{
+	pub fn add_control_flow_braces(&self, content: &String) -> (String, i32)
+	{
+		if self.config.control_flow_braces == ControlFlowBraces::Never
+		{
+			return (content.to_string(), 0);
+		}
+
+		let source = Source::new(content);
+		let masked_lines = &source.masked_lines;
+		let line_count = source.lines.len();
+		let mut edits = BraceEdits { lines: source.owned_lines(), closers: vec![Vec::new(); line_count], extra_indentation: vec![0; line_count] };
+
+		let mut missing_braces = 0;
+
+		for line_number in 0..line_count
+		{
+			let header = match control_header(&masked_lines[line_number])
+			{
+				Some(header) => header,
+				None => continue,
+			};
+
+			let rest = masked_lines[line_number][header.end..].trim();
+
+			if rest.is_empty()
+			{
+				if self.wrap_next_line_body(&mut edits, masked_lines, line_number, &header)
+				{
+					missing_braces += 1;
+				}
+			}
+			else if self.config.control_flow_braces == ControlFlowBraces::Always && is_same_line_body(rest) && header.keyword != "do"
+			{
+				self.wrap_same_line_body(&mut edits, line_number, &header);
+				missing_braces += 1;
+			}
+		}
+
+		let mut braced_content = String::from("");
+
+		for (line_number, line) in edits.lines.iter().enumerate()
+		{
+			let extra = edits.extra_indentation[line_number];
+			for part in line.split('\n')
+			{
+				if extra > 0 && !part.trim().is_empty()
+				{
+					braced_content.push_str(self.reindent(part, self.indentation_width(part) + extra).as_str());
+				}
+				else
+				{
+					braced_content.push_str(part);
+				}
+				braced_content.push_str("\n");
+			}
+			for closer in edits.closers[line_number].iter().rev()
+			{
+				braced_content.push_str(closer.as_str());
+				braced_content.push_str("\n");
+			}
+		}
+
+		return (braced_content, missing_braces);
+	}
+
+	/// Turns `if (x) y;` into a braced block spanning three lines
+	fn wrap_same_line_body(&self, edits: &mut BraceEdits, line_number: usize, header: &ControlHeader)
+	{
+		let line = &edits.lines[line_number];
+
+		if self.config.verbose
+		{
+			println!("Found missing control flow curly - {}", line);
+		}
+
+		let indentation = &line[..line.len() - line.trim_start().len()];
+		let width = self.indentation_width(line);
+
+		let mut header_line = String::from(line[..header.end].trim_end());
+		header_line.push_str(" {");
+		let body_line = self.reindent(&line[header.end..], width + self.config.indentation.size.max(1));
+
+		edits.lines[line_number] = format!("{}\n{}\n{}}}", header_line, body_line, indentation);
+	}
+
+	/// Wraps a body starting on the line after its header, returns false if the body could not be told apart
+	fn wrap_next_line_body(&self, edits: &mut BraceEdits, masked_lines: &[String], line_number: usize, header: &ControlHeader) -> bool
+	{
+		let body_start = match next_code_line(masked_lines, line_number + 1)
+		{
+			Some(start) => start,
+			None => return false,
+		};
+		if masked_lines[body_start].trim_start().starts_with('{')
+		{
+			return false;
+		}
+		let body_end = match statement_end(masked_lines, body_start)
+		{
+			Some(end) => end,
+			None => return false,
+		};
+
+		let line = edits.lines[line_number].clone();
+
+		if self.config.verbose
+		{
+			println!("Found missing control flow curly - {}", line);
+		}
+
+		let indentation = &line[..line.len() - line.trim_start().len()];
+		let width = self.indentation_width(&line);
+		let code_end = masked_lines[line_number].trim_end().len();
+
+		let mut header_line = String::from(&line[..code_end]);
+		header_line.push_str(" {");
+		let comment = line[code_end..].trim();
+		if !comment.is_empty()
+		{
+			header_line.push(' ');
+			header_line.push_str(comment);
+		}
+		edits.lines[line_number] = header_line;
+
+		// Bodies written at the same level as their header get indented
+		if self.indentation_width(&edits.lines[body_start]) <= width
+		{
+			for extra in edits.extra_indentation.iter_mut().take(body_end + 1).skip(body_start)
+			{
+				*extra += self.config.indentation.size.max(1);
+			}
+		}
+
+		// The `while` of a do-while loop goes right after the closing curly brace
+		let follows_do = header.keyword == "do" && body_end + 1 < edits.lines.len() && starts_with_word(masked_lines[body_end + 1].trim_start(), "while");
+		if follows_do
+		{
+			edits.lines[body_end + 1] = format!("{}}} {}", indentation, edits.lines[body_end + 1].trim_start());
+		}
+		else
+		{
+			let closer_width = width + edits.extra_indentation[line_number];
+			edits.closers[body_end].push(format!("{}}}", self.indentation_string(closer_width)));
+		}
+
+		return true;
+	}
+}
+
+/// Recognizes `if (...)`, `else if (...)`, `} else`, `for (...)`, `await for (...)`, `while (...)` and `do` at the start of a line
+fn control_header(masked: &str) -> Option<ControlHeader>
+{
+	let bytes = masked.as_bytes();
+	let mut position = skip_whitespace(bytes, 0);
+	let mut after_curly = false;
+
+	if bytes.get(position) == Some(&b'}')
+	{
+		position = skip_whitespace(bytes, position + 1);
+		after_curly = true;
+	}
+
+	let (word, after_word) = word_at(masked, position);
+	match word
+	{
+		"else" =>
+		{
+			let (next, after_next) = word_at(masked, skip_whitespace(bytes, after_word));
+			if next == "if"
+			{
+				return condition_end(bytes, after_next).map(|end| ControlHeader { keyword: "if", end });
+			}
+			return Some(ControlHeader { keyword: "else", end: after_word });
+		}
+		"if" if !after_curly => condition_end(bytes, after_word).map(|end| ControlHeader { keyword: "if", end }),
+		"for" if !after_curly => condition_end(bytes, after_word).map(|end| ControlHeader { keyword: "for", end }),
+		"while" if !after_curly => condition_end(bytes, after_word).map(|end| ControlHeader { keyword: "while", end }),
+		"await" if !after_curly =>
+		{
+			let (next, after_next) = word_at(masked, skip_whitespace(bytes, after_word));
+			if next != "for"
+			{
+				return None;
+			}
+			return condition_end(bytes, after_next).map(|end| ControlHeader { keyword: "for", end });
+		}
+		"do" if !after_curly => Some(ControlHeader { keyword: "do", end: after_word }),
+		_ => None,
+	}
+}
+
+/// A same line body is a single simple statement, nested control flow, multi-line statements and lines going on with
+/// another statement or an `else` like `if (x) return; else return y;` are left alone
+fn is_same_line_body(rest: &str) -> bool
+{
+	if rest.starts_with('{') || rest.starts_with(';') || !rest.ends_with(';') || control_header(rest).is_some()
+	{
+		return false;
+	}
+
+	let mut depth = 0;
+	let mut statements = 0;
+	for (i, b) in rest.bytes().enumerate()
+	{
+		match b
+		{
+			b'{' | b'(' | b'[' => depth += 1,
+			b'}' | b')' | b']' => depth -= 1,
+			b';' if depth == 0 => statements += 1,
+			b'e' if depth == 0 && (i == 0 || !is_identifier_byte(rest.as_bytes()[i - 1])) && starts_with_word(&rest[i..], "else") => return false,
+			_ =>
+			{}
+		}
+	}
+
+	return depth == 0 && statements == 1;
+}
+
+/// Finds the last line of the statement starting at the given line
+fn statement_end(masked_lines: &[String], start: usize) -> Option<usize>
+{
+	let masked = masked_lines[start].as_str();
+
+	if let Some(header) = control_header(masked)
+	{
+		let rest = masked[header.end..].trim();
+		let end = if rest.is_empty()
+		{
+			statement_end(masked_lines, next_code_line(masked_lines, start + 1)?)?
+		}
+		else if rest.starts_with('{')
+		{
+			block_end(masked_lines, start, masked.len() - masked[header.end..].trim_start().len())?
+		}
+		else
+		{
+			plain_statement_end(masked_lines, start, header.end)?
+		};
+
+		// An else either shares the line of the closing curly brace or starts the next one
+		let is_else_after_curly = end != start && masked_lines[end].trim_start().starts_with('}') && control_header(&masked_lines[end]).is_some();
+		let next = next_code_line(masked_lines, end + 1);
+		match (header.keyword, next)
+		{
+			("if", _) if is_else_after_curly => return statement_end(masked_lines, end),
+			("if", Some(next)) if starts_with_word(masked_lines[next].trim_start(), "else") => return statement_end(masked_lines, next),
+			("do", Some(next)) if starts_with_word(masked_lines[next].trim_start(), "while") => return Some(next),
+			_ => return Some(end),
+		}
+	}
+
+	if masked.trim_start().starts_with('{')
+	{
+		return block_end(masked_lines, start, masked.len() - masked.trim_start().len());
+	}
+
+	return plain_statement_end(masked_lines, start, 0);
+}
+
+/// Finds the line where a statement ends with a semicolon outside of any brackets
+fn plain_statement_end(masked_lines: &[String], start: usize, offset: usize) -> Option<usize>
+{
+	let mut depth = 0;
+
+	for (line_number, masked) in masked_lines.iter().enumerate().skip(start)
+	{
+		let code = if line_number == start { &masked[offset..] } else { masked.as_str() };
+		for b in code.bytes()
+		{
+			match b
+			{
+				b'{' | b'(' | b'[' => depth += 1,
+				b'}' | b')' | b']' => depth -= 1,
+				b';' if depth == 0 => return Some(line_number),
+				_ =>
+				{}
+			}
+			if depth < 0
+			{
+				return None;
+			}
+		}
+	}
+
+	return None;
+}
+
+/// Finds the line holding the curly brace that closes the one at the given position
+fn block_end(masked_lines: &[String], start: usize, position: usize) -> Option<usize>
+{
+	let mut depth = 0;
+
+	for (line_number, masked) in masked_lines.iter().enumerate().skip(start)
+	{
+		let code = if line_number == start { &masked[position..] } else { masked.as_str() };
+		for b in code.bytes()
+		{
+			match b
+			{
+				b'{' | b'(' | b'[' => depth += 1,
+				b'}' | b')' | b']' =>
+				{
+					depth -= 1;
+					if depth == 0
+					{
+						return Some(line_number);
+					}
+				}
+				_ =>
+				{}
+			}
+		}
+	}
+
+	return None;
+}
+
+fn next_code_line(masked_lines: &[String], start: usize) -> Option<usize>
+{
+	return (start..masked_lines.len()).find(|line_number| !masked_lines[*line_number].trim().is_empty());
+}
+
+fn condition_end(bytes: &[u8], position: usize) -> Option<usize>
+{
+	let position = skip_whitespace(bytes, position);
+	if bytes.get(position) != Some(&b'(')
+	{
+		return None;
+	}
+
+	let mut depth = 0;
+	for (i, b) in bytes.iter().enumerate().skip(position)
+	{
+		match b
+		{
+			b'(' => depth += 1,
+			b')' =>
+			{
+				depth -= 1;
+				if depth == 0
+				{
+					return Some(i + 1);
+				}
+			}
+			_ =>
+			{}
+		}
+	}
+
+	return None;
+}
+
+fn skip_whitespace(bytes: &[u8], position: usize) -> usize
+{
+	let mut position = position;
+	while position < bytes.len() && bytes[position].is_ascii_whitespace()
+	{
+		position += 1;
+	}
+	return position;
+}
+
+fn word_at(masked: &str, position: usize) -> (&str, usize)
+{
+	let bytes = masked.as_bytes();
+	let mut end = position;
+	while end < bytes.len() && is_identifier_byte(bytes[end])
+	{
+		end += 1;
+	}
+	return (&masked[position..end], end);
+}
@@ -0,0 +1,174 @@
+use crate::formatter::Formatter;
+use crate::scanner::is_identifier_byte;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub(crate) enum CurlyBraceKind
+{
+	/// Body of a declaration or control flow statement
+	Block,
+	/// Body of an anonymous function, like `setState(() {`
+	Closure,
+	/// Map or set literal, like `var map = {`
+	Collection,
+	/// Map pattern, or an expression like a switch expression within an arrow body
+	Expression,
+}
+
+const LITERAL_KEYWORDS: [&str; 6] = ["return", "yield", "const", "await", "in", "throw"];
+const STATEMENT_KEYWORDS: [&str; 4] = ["return", "yield", "await", "throw"];
+
+impl Formatter
+{
+	pub(crate) fn moves_curly_brace(&self, kind: CurlyBraceKind) -> bool
+	{
+		match kind
+		{
+			CurlyBraceKind::Block => true,
+			CurlyBraceKind::Closure => self.config.closure_curly_brace_on_next_line,
+			CurlyBraceKind::Collection => self.config.collection_curly_brace_on_next_line,
+			CurlyBraceKind::Expression => false,
+		}
+	}
+}
+
+/// Tells what a curly brace ending a line of masked code opens, given the code before it
+pub(crate) fn curly_brace_kind(code: &str) -> CurlyBraceKind
+{
+	let code = code.trim_end();
+	let words: Vec<&str> = code.split(|c: char| !c.is_alphanumeric() && c != '_').filter(|w| !w.is_empty()).collect();
+	let last_word = if code.ends_with(|c: char| c.is_alphanumeric() || c == '_') { words.last().copied().unwrap_or("") } else { "" };
+
+	if ["case", "var", "final"].contains(&last_word)
+	{
+		return CurlyBraceKind::Expression;
+	}
+	if LITERAL_KEYWORDS.contains(&last_word)
+	{
+		return CurlyBraceKind::Collection;
+	}
+
+	// Closures may be async or generators
+	let before_modifier = ["async*", "sync*", "async"].iter().find_map(|m| code.strip_suffix(m)).map(|c| c.trim_end()).unwrap_or(code);
+
+	if before_modifier.ends_with(')')
+	{
+		if let Some(open) = matching_open(before_modifier, b'(', b')')
+		{
+			if is_anonymous_parameter_list(&before_modifier[..open])
+			{
+				return CurlyBraceKind::Closure;
+			}
+			if let Some(before_switch) = before_modifier[..open].trim_end().strip_suffix("switch")
+			{
+				if is_switch_expression(before_switch)
+				{
+					return CurlyBraceKind::Expression;
+				}
+			}
+		}
+	}
+	else if code.ends_with('>')
+	{
+		// Type arguments of a literal like `<String, int>{`, as opposed to type parameters like `class Box<T> {`
+		if let Some(open) = matching_open(code, b'<', b'>')
+		{
+			if !code[..open].trim_end().ends_with(|c: char| c.is_alphanumeric() || c == '_')
+			{
+				return CurlyBraceKind::Collection;
+			}
+		}
+	}
+	else if code.ends_with(':')
+	{
+		let trimmed = code.trim_start();
+		let is_label = trimmed.starts_with("case ") || trimmed.starts_with("default");
+		if !is_label
+		{
+			return CurlyBraceKind::Collection;
+		}
+	}
+	else if code.ends_with("=>") || code.ends_with(['=', '(', '[', ',', '?', '{'])
+	{
+		return CurlyBraceKind::Collection;
+	}
+
+	let mut depth = 0;
+	let mut has_arrow = false;
+	for (i, c) in code.char_indices()
+	{
+		match c
+		{
+			'(' | '[' | '{' => depth += 1,
+			')' | ']' | '}' => depth -= 1,
+			'=' if depth == 0 && code[i..].starts_with("=>") => has_arrow = true,
+			_ =>
+			{}
+		}
+	}
+
+	if has_arrow || (depth > 0 && words.contains(&"case"))
+	{
+		return CurlyBraceKind::Expression;
+	}
+
+	return CurlyBraceKind::Block;
+}
+
+/// Whether the code right before an opening parenthesis makes it the parameter list of an anonymous function
+fn is_anonymous_parameter_list(before: &str) -> bool
+{
+	let before = before.trim_end();
+
+	// `operator ==(Object other)` declares an operator
+	let is_operator = before.ends_with(|c: char| !c.is_alphanumeric() && c != '_') && before.split(|c: char| !c.is_alphanumeric() && c != '_').any(|w| w == "operator");
+	if is_operator
+	{
+		return false;
+	}
+
+	// A closure may be returned, yielded, awaited or thrown, like `return (x) {`
+	let last_word = before.rsplit(|c: char| !c.is_alphanumeric() && c != '_').next().unwrap_or("");
+	if STATEMENT_KEYWORDS.contains(&last_word)
+	{
+		return true;
+	}
+
+	// Names and type parameters like `f<T>(` belong to declarations, calls and control flow
+	let last = before.as_bytes().last().copied();
+	return !last.is_some_and(|b| is_identifier_byte(b) || b >= 0x80 || b == b'>');
+}
+
+/// A switch is an expression when its value is used, like in `final r = switch (v) {`, and a statement otherwise
+fn is_switch_expression(before: &str) -> bool
+{
+	let before = before.trim_end();
+	if before.ends_with(|c: char| c.is_alphanumeric() || c == '_')
+	{
+		return before.rsplit(|c: char| !c.is_alphanumeric() && c != '_').next() == Some("return");
+	}
+	return before.ends_with("=>") || before.ends_with(['=', '(', ',']);
+}
+
+/// Finds the opener matching the closer that ends the code, if it is on the same line
+fn matching_open(code: &str, open: u8, close: u8) -> Option<usize>
+{
+	let mut depth = 0;
+
+	for (i, b) in code.bytes().enumerate().rev()
+	{
+		if b == close
+		{
+			depth += 1;
+		}
+		else if b == open
+		{
+			depth -= 1;
+			if depth == 0
+			{
+				return Some(i);
+			}
+		}
+	}
+
+	return None;
+}
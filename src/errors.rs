@@ -0,0 +1,104 @@
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Exit codes, following the BSD sysexits where there's one for it
+pub(crate) const EXIT_FORMATTING_NEEDED: i32 = 1;
+/// Same as clap uses for invalid arguments
+pub(crate) const EXIT_USAGE_ERROR: i32 = 2;
+pub(crate) const EXIT_PARSE_ERROR: i32 = 65;
+pub(crate) const EXIT_IO_ERROR: i32 = 74;
+
+#[derive(Debug)]
+pub(crate) enum BlinkError
+{
+	/// Reading, writing or listing a file or folder failed
+	Io
+	{
+		path: PathBuf, action: &'static str, error: io::Error
+	},
+	/// The content isn't text blink can make sense of, like a file that isn't valid UTF-8
+	Parse
+	{
+		path: PathBuf, reason: String
+	},
+}
+
+impl BlinkError
+{
+	/// Reading text that turns out not to be valid UTF-8 is a parse error rather than an I/O one
+	pub(crate) fn from_io(path: &Path, action: &'static str, error: io::Error) -> BlinkError
+	{
+		if error.kind() == io::ErrorKind::InvalidData
+		{
+			return BlinkError::Parse { path: path.to_path_buf(), reason: error.to_string() };
+		}
+		return BlinkError::Io { path: path.to_path_buf(), action, error };
+	}
+
+	pub(crate) fn exit_code(&self) -> i32
+	{
+		match self
+		{
+			BlinkError::Io { .. } => EXIT_IO_ERROR,
+			BlinkError::Parse { .. } => EXIT_PARSE_ERROR,
+		}
+	}
+}
+
+impl fmt::Display for BlinkError
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		match self
+		{
+			BlinkError::Io { path, action, error } => write!(f, "Unable to {} `{}`\nReason: {}", action, path.display(), error),
+			BlinkError::Parse { path, reason } => write!(f, "Unable to parse `{}`\nReason: {}", path.display(), reason),
+		}
+	}
+}
+
+/// What happened to the files of a run, to sum it up and pick the exit code at the end
+#[derive(Default)]
+pub(crate) struct Report
+{
+	pub(crate) files: usize,
+	/// Files that would change, only counted on dry runs
+	pub(crate) unformatted: usize,
+	errors: Vec<BlinkError>,
+}
+
+impl Report
+{
+	pub(crate) fn add_error(&mut self, error: BlinkError)
+	{
+		eprintln!("Error: {}", error);
+		self.errors.push(error);
+	}
+
+	pub(crate) fn print_summary(&self)
+	{
+		if !self.errors.is_empty()
+		{
+			eprintln!("Failed to format {} of {} files", self.errors.len(), self.files.max(self.errors.len()));
+		}
+		if self.unformatted > 0
+		{
+			eprintln!("{} of {} files need formatting", self.unformatted, self.files);
+		}
+	}
+
+	/// I/O errors win over parse errors, which win over files needing formatting
+	pub(crate) fn exit_code(&self) -> i32
+	{
+		if let Some(code) = self.errors.iter().map(BlinkError::exit_code).max()
+		{
+			return code;
+		}
+		if self.unformatted > 0
+		{
+			return EXIT_FORMATTING_NEEDED;
+		}
+		return 0;
+	}
+}
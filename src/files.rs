@@ -0,0 +1,96 @@
+use std::fs::{self, File, Permissions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Replaces the file through a temporary file next to it, so it's never left half written, and leaves it alone when nothing changed.
+/// Symbolic links are kept and the file they point to is written instead. Gives whether the file was written.
+pub(crate) fn write_atomically(path: &Path, content: &str) -> io::Result<bool>
+{
+	let target = resolve_symlinks(path)?;
+
+	if fs::read(&target).is_ok_and(|existing| existing == content.as_bytes())
+	{
+		return Ok(false);
+	}
+
+	let permissions = fs::metadata(&target).ok().map(|metadata| metadata.permissions());
+	let file_name = target.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+	let temporary = target.with_file_name(format!(".{}.blink-{}", file_name, std::process::id()));
+
+	let res = write_and_rename(&temporary, &target, content, permissions);
+	if res.is_err()
+	{
+		let _ = fs::remove_file(&temporary);
+	}
+
+	return res.map(|_| true);
+}
+
+/// Reads paths separated by NUL characters, as given by `git diff --name-only -z`, or else one per line
+pub(crate) fn read_file_list(path: &Path) -> io::Result<Vec<PathBuf>>
+{
+	let mut content = String::new();
+	if path.as_os_str() == "-"
+	{
+		io::stdin().read_to_string(&mut content)?;
+	}
+	else
+	{
+		content = fs::read_to_string(path)?;
+	}
+
+	let separator = if content.contains('\0') { '\0' } else { '\n' };
+	return Ok(content.split(separator).map(|line| line.trim_end_matches('\r')).filter(|line| !line.is_empty()).map(PathBuf::from).collect());
+}
+
+pub(crate) fn create_parent_folders(path: &Path) -> io::Result<()>
+{
+	match path.parent()
+	{
+		Some(parent) if !parent.as_os_str().is_empty() => fs::create_dir_all(parent),
+		_ => Ok(()),
+	}
+}
+
+fn write_and_rename(temporary: &Path, target: &Path, content: &str, permissions: Option<Permissions>) -> io::Result<()>
+{
+	let mut file = File::create(temporary)?;
+	file.write_all(content.as_bytes())?;
+	if let Some(permissions) = permissions
+	{
+		file.set_permissions(permissions)?;
+	}
+	file.sync_all()?;
+
+	return fs::rename(temporary, target);
+}
+
+fn resolve_symlinks(path: &Path) -> io::Result<PathBuf>
+{
+	match fs::symlink_metadata(path)
+	{
+		Ok(metadata) if metadata.file_type().is_symlink() => fs::canonicalize(path),
+		_ => Ok(path.to_path_buf()),
+	}
+}
+
+/// The closest folder holding a `pubspec.yaml`, or else the root of the git repository
+pub(crate) fn find_project_root(starting_path: &Path) -> Option<PathBuf>
+{
+	let start = fs::canonicalize(starting_path).ok()?;
+	let mut current_path = if start.is_dir() { start } else { start.parent()?.to_path_buf() };
+
+	loop
+	{
+		if current_path.join("pubspec.yaml").is_file() || current_path.join(".git").is_dir()
+		{
+			return Some(current_path);
+		}
+
+		match current_path.parent()
+		{
+			Some(parent) => current_path = parent.to_path_buf(),
+			None => return None,
+		}
+	}
+}
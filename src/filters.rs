@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+
+use crate::files::find_project_root;
+
+/// Picks the files to format by their extension and the `--include` and `--exclude` globs given on the command line
+pub(crate) struct Filters
+{
+	/// Files have to match one of these if there are any
+	include: Option<GlobSet>,
+	exclude: GlobSet,
+	extensions: Vec<String>,
+	/// Project root of each folder looked at so far, globs match paths relative to it
+	roots: HashMap<PathBuf, Option<PathBuf>>,
+}
+
+impl Filters
+{
+	pub(crate) fn new(include: &[String], exclude: &[String], extensions: &[String]) -> Result<Filters, globset::Error>
+	{
+		let include = if include.is_empty() { None } else { Some(glob_set(include)?) };
+		let exclude = glob_set(exclude)?;
+
+		let mut all_extensions = vec![String::from("dart")];
+		for extension in extensions
+		{
+			let extension = extension.trim().trim_start_matches('.');
+			if !extension.is_empty() && !all_extensions.iter().any(|e| e == extension)
+			{
+				all_extensions.push(extension.to_string());
+			}
+		}
+
+		return Ok(Filters { include, exclude, extensions: all_extensions, roots: HashMap::new() });
+	}
+
+	pub(crate) fn has_extension(&self, path: &Path) -> bool
+	{
+		let file_name = file_name(path);
+		return self.extensions.iter().any(|extension| file_name.len() > extension.len() + 1 && file_name.ends_with(extension.as_str()) && file_name[..file_name.len() - extension.len()].ends_with('.'));
+	}
+
+	/// Files generated by build_runner, like `user.g.dart`
+	pub(crate) fn is_generated(&self, path: &Path) -> bool
+	{
+		let file_name = file_name(path);
+		return self.extensions.iter().any(|extension| file_name.ends_with(format!(".g.{}", extension).as_str()));
+	}
+
+	pub(crate) fn is_excluded(&mut self, path: &Path) -> bool
+	{
+		let path = self.project_path(path);
+		return self.exclude.is_match(path);
+	}
+
+	pub(crate) fn is_included(&mut self, path: &Path) -> bool
+	{
+		if self.include.is_none()
+		{
+			return true;
+		}
+		let path = self.project_path(path);
+		return self.include.as_ref().is_some_and(|include| include.is_match(path));
+	}
+
+	/// The path relative to its project root, so `lib/`, `./lib/` and an absolute path match the same globs
+	fn project_path(&mut self, path: &Path) -> PathBuf
+	{
+		let absolute = match fs::canonicalize(path)
+		{
+			Ok(absolute) => absolute,
+			Err(_) => return path.strip_prefix(".").unwrap_or(path).to_path_buf(),
+		};
+
+		let folder = if absolute.is_dir() { absolute.clone() } else { absolute.parent().map(|parent| parent.to_path_buf()).unwrap_or_default() };
+		let root = self.roots.entry(folder).or_insert_with_key(|folder| find_project_root(folder).or_else(|| std::env::current_dir().ok().and_then(|dir| fs::canonicalize(dir).ok())));
+
+		return match root
+		{
+			Some(root) => absolute.strip_prefix(root).map(|relative| relative.to_path_buf()).unwrap_or(absolute),
+			None => absolute,
+		};
+	}
+}
+
+fn glob_set(globs: &[String]) -> Result<GlobSet, globset::Error>
+{
+	let mut builder = GlobSetBuilder::new();
+	for glob in globs
+	{
+		builder.add(Glob::new(glob)?);
+	}
+	return builder.build();
+}
+
+fn file_name(path: &Path) -> String
+{
+	return path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+}
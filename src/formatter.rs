@@ -1,64 +1,83 @@
-use crate::config::{self, IndentationStyle};
-use regex::Regex;
-use std::collections::HashMap;
+use crate::config::{self, IndentationStyle, LineEnding};
+use crate::curly_braces;
+use crate::scanner;
+use crate::source::Source;
 use substring::Substring;
 
-pub(crate) struct Formatter
+pub struct Formatter
 {
-	pub(crate) config: config::Config,
+	pub config: config::Config,
 }
 
-pub(crate) struct FormatterResult
+pub struct FormatterResult
 {
-	pub(crate) content: String,
-	pub(crate) incorrect_curly_braces: i32,
-	pub(crate) incorrect_indentations: i32,
-	pub(crate) incorrect_quotes: i32,
-	pub(crate) incorrect_else_placements: i32,
-	pub(crate) incorrect_break_placements: i32,
-}
-
-struct IncorrectSwitchBreakIndentation
-{
-	line: i32,
-	indent: String,
-}
-
-struct SwitchLines
-{
-	start_line: i32,
-	end_line: i32,
-	indent: String,
+	pub content: String,
+	pub incorrect_curly_braces: i32,
+	pub incorrect_indentations: i32,
+	pub incorrect_quotes: i32,
+	pub incorrect_else_placements: i32,
+	pub incorrect_break_placements: i32,
+	pub incorrect_switch_indentations: i32,
+	pub incorrect_trailing_commas: i32,
+	pub incorrect_imports: i32,
+	pub incorrect_operator_spacings: i32,
+	pub incorrect_comma_spacings: i32,
+	pub incorrect_call_paren_spacings: i32,
+	pub incorrect_keyword_spacings: i32,
+	pub missing_control_flow_braces: i32,
+	pub incorrect_arrow_indentations: i32,
+	pub converted_function_bodies: i32,
+	pub incorrect_widget_indentations: i32,
+	pub incorrect_method_chains: i32,
 }
 
 impl Formatter
 {
-	pub(crate) fn format(&self, content: String) -> FormatterResult
+	pub fn format(&self, content: String) -> FormatterResult
 	{
 		let mut incorrect_curly_braces = 0;
 		let mut incorrect_indentations = 0;
 		let mut incorrect_quotes = 0;
 		let mut incorrect_else_placements = 0;
 		let mut incorrect_break_placements = 0;
+		let mut incorrect_operator_spacings = 0;
+		let mut incorrect_comma_spacings = 0;
+		let mut incorrect_call_paren_spacings = 0;
+		let mut incorrect_keyword_spacings = 0;
+
+		let line_ending = self.config.end_of_line.unwrap_or(detect_line_ending(&content));
+		let ends_with_newline = content.ends_with('\n') || content.ends_with('\r');
+		let content = normalize_line_endings(content);
+		let (content, missing_control_flow_braces) = self.add_control_flow_braces(&content);
 
-		let forbidden_lines = self.forbidden_lines(&content);
+		let source = Source::new(&content);
 
 		let mut line_number = 0;
 
 		let mut fixed_content = String::from("");
 
-		for line in content.lines()
+		for line in source.lines.iter().copied()
 		{
-			if forbidden_lines.contains(&line_number)
+			if source.protected.contains(line_number)
 			{
-				fixed_content.push_str(&line);
+				let ends_inside_string = matches!(source.states.get(line_number + 1), Some(scanner::LineState::MultiLineString(..)));
+				if self.config.trim_trailing_whitespace && !ends_inside_string
+				{
+					fixed_content.push_str(line.trim_end());
+				}
+				else
+				{
+					fixed_content.push_str(line);
+				}
 				fixed_content.push_str("\n");
 
 				line_number += 1;
 				continue;
 			}
 
-			let (fline1, changed1) = self.fix_incorrect_curly_braces(line.trim_end().to_string());
+			let trailing_whitespace = if self.config.trim_trailing_whitespace { "" } else { &line[line.trim_end().len()..] };
+
+			let (fline1, changed1) = self.fix_incorrect_curly_braces(line.trim_end().to_string(), source.states[line_number]);
 			if changed1
 			{
 				incorrect_curly_braces += 1;
@@ -70,7 +89,7 @@ impl Formatter
 				incorrect_indentations += 1;
 			}
 
-			let (fline3, changed3) = self.fix_incorrect_quotes(fline2);
+			let (fline3, changed3) = self.fix_incorrect_quotes(fline2, source.states[line_number]);
 			if changed3
 			{
 				incorrect_quotes += 1;
@@ -88,403 +107,213 @@ impl Formatter
 				incorrect_break_placements += 1;
 			}
 
-			fixed_content.push_str(&fline5);
+			let (fline6, spacings) = self.fix_incorrect_spacing(fline5, source.states[line_number]);
+			incorrect_operator_spacings += spacings.operators;
+			incorrect_comma_spacings += spacings.commas;
+			incorrect_call_paren_spacings += spacings.call_parens;
+			incorrect_keyword_spacings += spacings.keywords;
+
+			fixed_content.push_str(&fline6);
+			fixed_content.push_str(trailing_whitespace);
 			fixed_content.push_str("\n");
 
 			line_number += 1;
 		}
 
-		let cleaned_content1 = self.remove_repeating_empty_lines(&fixed_content);
-		let cleaned_content2 = self.remove_preceeding_empty_lines(&cleaned_content1);
-		let cleaned_content3 = self.correct_switch_break_indentations(&cleaned_content2);
-		let cleaned_content4 = self.correct_weird_elses(&cleaned_content3);
+		let (joined_content, joined_curly_braces, joined_else_placements) = self.join_curly_braces_and_elses(&fixed_content);
+		let (converted_content, converted_function_bodies) = self.convert_function_bodies(&joined_content);
+		let (indented_content, incorrect_arrow_indentations) = self.indent_arrow_bodies(&converted_content);
+		let (organized_content, incorrect_imports) = self.organize_imports(&indented_content);
+		let cleaned_content1 = self.apply_blank_line_policy(&organized_content);
+		let (cleaned_content2, incorrect_switch_indentations) = self.correct_switch_indentations(&cleaned_content1);
+		let (widget_content, incorrect_widget_indentations) = self.indent_widget_trees(&cleaned_content2);
+		let (chained_content, incorrect_method_chains) = self.format_method_chains(&widget_content);
+		let cleaned_content3 = self.correct_weird_elses(&chained_content);
+		let (cleaned_content4, incorrect_trailing_commas) = self.fix_trailing_commas(&cleaned_content3);
+		let finished_content = self.apply_line_endings(cleaned_content4, line_ending, ends_with_newline);
+
+		incorrect_curly_braces += joined_curly_braces;
+		incorrect_else_placements += joined_else_placements;
 
 		// if self.config.use_treesitter_to_format
 		// {
 		// 	return FormatterResult { content: self.format_using_treesitter(cleaned_content4), incorrect_curly_braces, incorrect_indentations, incorrect_quotes, incorrect_else_placements, incorrect_break_placements };
 		// }
 
-		return FormatterResult { content: cleaned_content4, incorrect_curly_braces, incorrect_indentations, incorrect_quotes, incorrect_else_placements, incorrect_break_placements };
+		return FormatterResult {
+			content: finished_content,
+			incorrect_curly_braces,
+			incorrect_indentations,
+			incorrect_quotes,
+			incorrect_else_placements,
+			incorrect_break_placements,
+			incorrect_switch_indentations,
+			incorrect_trailing_commas,
+			incorrect_imports,
+			incorrect_operator_spacings,
+			incorrect_comma_spacings,
+			incorrect_call_paren_spacings,
+			incorrect_keyword_spacings,
+			missing_control_flow_braces,
+			incorrect_arrow_indentations,
+			converted_function_bodies,
+			incorrect_widget_indentations,
+			incorrect_method_chains,
+		};
 	}
 
-	fn forbidden_lines(&self, content: &String) -> Vec<i32>
+	fn apply_line_endings(&self, content: String, line_ending: LineEnding, ends_with_newline: bool) -> String
 	{
-		let mut forbidden: Vec<i32> = Vec::new();
+		let mut finished_content = content;
 
-		let mut line_number = 0;
-
-		let squotes = "'''";
-		let mut is_inside_squotes = false;
-
-		for line in content.lines()
+		if !self.config.insert_final_newline && !ends_with_newline && finished_content.ends_with('\n')
 		{
-			if line.contains(squotes)
-			{
-				if is_inside_squotes
-				{
-					is_inside_squotes = false;
-					forbidden.push(line_number);
-				}
-				else
-				{
-					is_inside_squotes = true;
-					forbidden.push(line_number);
-				}
-			}
-			else if is_inside_squotes
-			{
-				forbidden.push(line_number);
-			}
-			line_number += 1;
+			finished_content.pop();
 		}
 
-		let mut is_inside_dquotes = false;
-		let dquotes = "\"\"\"";
-		line_number = 0;
-
-		for line in content.lines()
+		if line_ending != LineEnding::Lf
 		{
-			if line.trim().starts_with("//")
-			{
-				forbidden.push(line_number);
-				line_number += 1;
-				continue;
-			}
-			if line.contains(dquotes)
-			{
-				if is_inside_dquotes
-				{
-					is_inside_dquotes = false;
-					forbidden.push(line_number);
-				}
-				else
-				{
-					is_inside_dquotes = true;
-					forbidden.push(line_number);
-				}
-			}
-			else if is_inside_dquotes
-			{
-				forbidden.push(line_number);
-			}
-			line_number += 1;
+			finished_content = finished_content.replace('\n', line_ending.as_str());
 		}
 
-		return forbidden;
-	}
-
-	fn empty_lines(&self, content: &String) -> Vec<i32>
-	{
-		let mut empty: Vec<i32> = Vec::new();
-
-		let mut line_number = 0;
-
-		for line in content.lines()
-		{
-			if line.is_empty() || line.trim().eq("{")
-			{
-				empty.push(line_number);
-			}
-			line_number += 1;
-		}
-
-		return empty;
-	}
-
-	fn empty_lines_preceeding_end_curly(&self, content: &String) -> Vec<i32>
-	{
-		let mut empty: Vec<i32> = Vec::new();
-
-		let mut line_number = 0;
-
-		let mut was_previous_line_empty = false;
-
-		for line in content.lines()
-		{
-			if line.is_empty()
-			{
-				was_previous_line_empty = true;
-			}
-			else if was_previous_line_empty
-			{
-				if line.trim().eq("}")
-				{
-					empty.push(line_number - 1);
-				}
-				was_previous_line_empty = false;
-			}
-
-			line_number += 1;
-		}
-
-		return empty;
-	}
-
-	fn remove_repeating_empty_lines(&self, content: &String) -> String
-	{
-		let forbidden_lines = self.forbidden_lines(&content);
-		let empty_lines = self.empty_lines(&content);
-
-		let mut line_number = 0;
-		let mut previous_line_number = -1;
-
-		let mut cleaned_content = String::from("");
-
-		for line in content.lines()
-		{
-			if forbidden_lines.contains(&line_number)
-			{
-				cleaned_content.push_str(&line);
-				cleaned_content.push_str("\n");
-				line_number += 1;
-				previous_line_number += 1;
-				continue;
-			}
-
-			if line_number > 0 && empty_lines.contains(&line_number) && empty_lines.contains(&previous_line_number)
-			{
-				line_number += 1;
-				previous_line_number += 1;
-				continue;
-			}
-
-			cleaned_content.push_str(&line);
-			cleaned_content.push_str("\n");
-			line_number += 1;
-			previous_line_number += 1;
-		}
-
-		return cleaned_content;
-	}
-
-	fn remove_preceeding_empty_lines(&self, content: &String) -> String
-	{
-		let forbidden_lines = self.forbidden_lines(&content);
-		let empty_preceed_lines = self.empty_lines_preceeding_end_curly(&content);
-
-		let mut line_number = 0;
-
-		let mut cleaned_content = String::from("");
-
-		for line in content.lines()
-		{
-			if forbidden_lines.contains(&line_number)
-			{
-				cleaned_content.push_str(&line);
-				cleaned_content.push_str("\n");
-				line_number += 1;
-				continue;
-			}
-
-			if empty_preceed_lines.contains(&line_number)
-			{
-				line_number += 1;
-				continue;
-			}
-
-			cleaned_content.push_str(&line);
-			cleaned_content.push_str("\n");
-			line_number += 1;
-		}
-
-		return cleaned_content;
+		return finished_content;
 	}
 
 	fn correct_weird_elses(&self, content: &String) -> String
 	{
-		let forbidden_lines = self.forbidden_lines(&content);
-
-		let mut line_number = 0;
+		let source = Source::new(content);
 
 		let mut cleaned_content = String::from("");
 
-		for line in content.lines()
+		for (line_number, line) in source.lines.iter().enumerate()
 		{
-			if forbidden_lines.contains(&line_number)
+			if source.protected.contains(line_number)
 			{
-				cleaned_content.push_str(&line);
+				cleaned_content.push_str(line);
 				cleaned_content.push_str("\n");
-				line_number += 1;
 				continue;
 			}
 
-			let (fixed_line, _) = self.fix_incorrect_else_placement(String::from(line));
+			let (fixed_line, _) = self.fix_incorrect_else_placement(String::from(*line));
 
 			cleaned_content.push_str(fixed_line.as_str());
 			cleaned_content.push_str("\n");
-			line_number += 1;
 		}
 
 		return cleaned_content;
 	}
 
-	fn incorrect_switch_break_indendation_lines(&self, content: &String) -> Vec<IncorrectSwitchBreakIndentation>
+	fn join_curly_braces_and_elses(&self, content: &String) -> (String, i32, i32)
 	{
-		let forbidden_lines = self.forbidden_lines(&content);
-
-		let mut wrong: Vec<IncorrectSwitchBreakIndentation> = Vec::new();
+		if self.config.curly_brace_on_next_line
+		{
+			return (content.to_string(), 0, 0);
+		}
 
-		let mut line_number = 0;
+		let source = Source::new(content);
 
-		let last_case_line = -1;
+		let mut incorrect_curly_braces = 0;
+		let mut incorrect_else_placements = 0;
 
-		let mut switches: Vec<SwitchLines> = Vec::new();
-		let switch_regex = Regex::new(r"^\s*switch\s*\(.*\)\s*\{?\s*$").unwrap();
-		let open_brace_regex = Regex::new(r"\{").unwrap();
-		let close_brace_regex = Regex::new(r"\}").unwrap();
+		// (line, masked line, is forbidden)
+		let mut joined: Vec<(String, String, bool)> = Vec::new();
 
-		let mut map: HashMap<i32, String> = HashMap::new();
-		let mut stack: Vec<i32> = Vec::new();
-		let mut braces: HashMap<i32, Vec<i32>> = HashMap::new();
-		for line in content.lines()
+		for (line_number, line) in source.lines.iter().enumerate()
 		{
-			if forbidden_lines.contains(&line_number)
-			{
-				line_number += 1;
-				continue;
-			}
-
-			if switch_regex.is_match(line)
-			{
-				let delta = line.len() - line.trim_start().len();
-				let indent = line.substring(0, delta);
-				stack.push(line_number);
-				braces.insert(line_number, Vec::new());
-				map.insert(line_number, indent.to_string());
-			}
+			let masked = &source.masked_lines[line_number];
+			let is_forbidden = source.protected.contains(line_number);
 
-			if !stack.is_empty()
+			if !is_forbidden
 			{
-				if open_brace_regex.is_match(line) && close_brace_regex.is_match(line)
+				if let Some((pline, pmasked, false)) = joined.last()
 				{
-				}
-				else
-				{
-					if open_brace_regex.is_match(line)
-					{
-						let stackb = braces.get_mut(stack.last().unwrap()).expect("Getting a braces stack for the switch block {");
-						stackb.push(line_number);
-					}
+					let pcode = pmasked.trim_end();
 
-					if close_brace_regex.is_match(line)
+					if masked.trim() == "{" && !pcode.trim().is_empty() && !pcode.ends_with([';', '{', '}', ',', '[', '('])
 					{
-						let stackb = braces.get_mut(stack.last().unwrap()).expect("Getting a braces stack for the switch block }");
-						stackb.pop();
-						if stackb.is_empty()
+						if self.config.verbose
 						{
-							if let Some(start_line) = stack.pop()
-							{
-								let indent = map.get(&start_line).unwrap();
-								switches.push(SwitchLines { start_line, end_line: line_number, indent: indent.to_string() });
-							}
+							println!("Found incorrect curly - {}", line);
 						}
-					}
-				}
-			}
-			line_number += 1;
-		}
 
-		line_number = 0;
-		let mut active_switch: Option<usize> = None;
-		let mut active_switches: Vec<&SwitchLines> = Vec::new();
-		switches.sort_by_key(|switch| switch.start_line);
-		for line in content.lines()
-		{
-			if forbidden_lines.contains(&line_number)
-			{
-				line_number += 1;
-				continue;
-			}
-			if switch_regex.is_match(line)
-			{
-				if let Some(index) = active_switch
-				{
-					active_switch = Some(index + 1);
-				}
-				else
-				{
-					active_switch = Some(0);
-				}
-				if let Some(sw) = switches.get(active_switch.unwrap())
-				{
-					active_switches.push(sw);
-				}
-			}
-			else
-			{
-				if let Some(sw) = active_switches.last()
-				{
-					if line.trim().contains("case ") && !line.contains(" break;") && !line.contains(" return;") && !line.contains(" return ")
-					{
-					}
-					else
-					{
-						if line.trim().starts_with("break;")
-						{
-							wrong.push(IncorrectSwitchBreakIndentation { line: line_number, indent: sw.indent.to_string() + "\t" });
-						}
-						else if line.trim().starts_with("return;") || line.contains(" return;") || line.contains(" return ")
+						let brace = line.find('{').unwrap_or(0);
+						let comments: Vec<&str> = vec![pline[pcode.len()..].trim(), line[brace + 1..].trim()].into_iter().filter(|c| !c.is_empty()).collect();
+
+						let mut s = String::from(&pline[..pcode.len()]);
+						s.push_str(" {");
+						let m = s.clone();
+						if !comments.is_empty()
 						{
+							s.push(' ');
+							s.push_str(comments.join(" ").as_str());
 						}
+
+						joined.pop();
+						joined.push((s, m, false));
+						incorrect_curly_braces += 1;
+						continue;
 					}
-					if sw.end_line == line_number
+
+					let tmasked = masked.trim_start();
+					let follows_curly = ["else", "catch", "on", "finally"].iter().any(|k| tmasked.starts_with(k) && !tmasked[k.len()..].starts_with(|c: char| c.is_alphanumeric() || c == '_'));
+
+					if follows_curly && pline.trim() == "}"
 					{
-						active_switches.pop();
+						if self.config.verbose
+						{
+							println!("Found incorrect else - {}", line);
+						}
+
+						let delta = pline.len() - pline.trim_start().len();
+
+						let mut s = String::from(&pline[..delta]);
+						s.push_str("} ");
+						let mut m = s.clone();
+						s.push_str(line.trim_start());
+						m.push_str(tmasked);
+
+						joined.pop();
+						joined.push((s, m, false));
+						incorrect_else_placements += 1;
+						continue;
 					}
 				}
 			}
-			line_number += 1;
-		}
 
-		return wrong;
-	}
-
-	fn correct_switch_break_indentations(&self, content: &String) -> String
-	{
-		let switch_breaks = self.incorrect_switch_break_indendation_lines(&content);
-
-		let mut line_number = 0;
+			joined.push((line.to_string(), masked.to_string(), is_forbidden));
+		}
 
 		let mut cleaned_content = String::from("");
 
-		let mut correction_needed = false;
-
-		for line in content.lines()
+		for (line, _, _) in joined
 		{
-			for br in &switch_breaks
-			{
-				if br.line == line_number
-				{
-					cleaned_content.push_str(&br.indent);
-					cleaned_content.push_str(&line.trim_start());
-					cleaned_content.push_str("\n");
-					correction_needed = true;
-					break;
-				}
-			}
-			if !correction_needed
-			{
-				cleaned_content.push_str(&line);
-				cleaned_content.push_str("\n");
-			}
-			correction_needed = false;
-			line_number += 1;
+			cleaned_content.push_str(line.as_str());
+			cleaned_content.push_str("\n");
 		}
 
-		return cleaned_content;
+		return (cleaned_content, incorrect_curly_braces, incorrect_else_placements);
 	}
 
-	fn fix_incorrect_curly_braces(&self, line: String) -> (String, bool)
+	fn fix_incorrect_curly_braces(&self, line: String, state: scanner::LineState) -> (String, bool)
 	{
-		if self.config.curly_brace_on_next_line && line.ends_with("{")
+		if !self.config.curly_brace_on_next_line
 		{
-			let line_length = line.len();
-			let rline = line.substring(0, line_length - 1);
+			return (line, false);
+		}
+
+		let (masked, _) = scanner::mask_line(&line, state);
+		let code = masked.trim_end();
+
+		if code.ends_with("{") && self.moves_curly_brace(curly_braces::curly_brace_kind(&code[..code.len() - 1]))
+		{
+			let brace = code.len() - 1;
+			let rline = line.substring(0, brace);
+			let comment = line[brace + 1..].trim();
 			let tline = rline.trim_start();
-			let is_incorrect = tline.len() > 0;
+			let is_incorrect = !tline.is_empty();
 			if is_incorrect
 			{
-				let delta = line_length - line.trim_start().len();
+				let delta = line.len() - line.trim_start().len();
 
 				if self.config.verbose
 				{
@@ -492,6 +321,11 @@ impl Formatter
 				}
 
 				let mut s = String::from(rline.trim_end());
+				if !comment.is_empty()
+				{
+					s.push_str(" ");
+					s.push_str(comment);
+				}
 				s.push_str("\n");
 
 				let mut s2 = String::from(line.substring(0, delta));
@@ -510,7 +344,11 @@ impl Formatter
 	{
 		if self.config.curly_brace_on_next_line && line.contains("} else")
 		{
-			if line.ends_with("} else") || line.contains("} else ")
+			// Only an else in code counts, not one inside a string or comment
+			let (masked, _) = scanner::mask_line(&line, scanner::LineState::Code);
+			let positions: Vec<usize> = masked.match_indices("} else").map(|(i, _)| i).filter(|i| masked[i + 6..].is_empty() || masked[i + 6..].starts_with(' ')).collect();
+
+			if !positions.is_empty()
 			{
 				let line_length = line.len();
 				let delta = line_length - line.trim_start().len();
@@ -520,7 +358,17 @@ impl Formatter
 				s.push_str(pre);
 				s.push_str("else");
 
-				return (line.replace("} else", s.as_str()), true);
+				let mut fixed = String::from("");
+				let mut last = 0;
+				for position in positions
+				{
+					fixed.push_str(&line[last..position]);
+					fixed.push_str(s.as_str());
+					last = position + 6;
+				}
+				fixed.push_str(&line[last..]);
+
+				return (fixed, true);
 			}
 
 			return (line, false);
@@ -550,6 +398,52 @@ impl Formatter
 		return (line, false);
 	}
 
+	/// Width of the leading whitespace of a line, a tab counts as one indentation level
+	pub(crate) fn indentation_width(&self, line: &str) -> usize
+	{
+		let size = self.config.indentation.size.max(1);
+		let mut width = 0;
+
+		for c in line.chars()
+		{
+			match c
+			{
+				'\t' => width += size,
+				' ' => width += 1,
+				_ => break,
+			}
+		}
+
+		return width;
+	}
+
+	pub(crate) fn indentation_string(&self, width: usize) -> String
+	{
+		let size = self.config.indentation.size.max(1);
+
+		match self.config.indentation.style
+		{
+			IndentationStyle::Tabs =>
+			{
+				let mut s = "\t".repeat(width / size);
+				s.push_str(" ".repeat(width % size).as_str());
+				return s;
+			}
+			IndentationStyle::Spaces =>
+			{
+				return " ".repeat(width);
+			}
+		}
+	}
+
+	/// Replaces the leading whitespace of a line with the given indentation width
+	pub(crate) fn reindent(&self, line: &str, width: usize) -> String
+	{
+		let mut s = self.indentation_string(width);
+		s.push_str(line.trim_start());
+		return s;
+	}
+
 	fn fix_incorrect_indentation(&self, line: String, verbose: bool) -> (String, bool)
 	{
 		match self.config.indentation.style
@@ -591,30 +485,144 @@ impl Formatter
 		return (line, false);
 	}
 
-	fn fix_incorrect_quotes(&self, line: String) -> (String, bool)
+	fn fix_incorrect_quotes(&self, line: String, state: scanner::LineState) -> (String, bool)
 	{
 		if self.config.prefer_double_quotes && line.contains("'") && !line.starts_with("import '") && !line.starts_with("export '")
 		{
-			let mut number_of_singles = 0;
-			let mut number_of_doubles = 0;
-			for char in line.chars()
+			return self.convert_quotes(line, state, b'\'', b'"');
+		}
+		if !self.config.prefer_double_quotes && line.contains('"')
+		{
+			return self.convert_quotes(line, state, b'"', b'\'');
+		}
+		return (line, false);
+	}
+
+	/// Converts the string literals of a line starting in the given scanner state, so a line within a block comment keeps its quotes
+	pub(crate) fn convert_quotes(&self, line: String, state: scanner::LineState, from: u8, to: u8) -> (String, bool)
+	{
+		let (regions, _) = scanner::scan_line(&line, state);
+
+		// Adjacent literals are concatenated, so they are converted all together or not at all
+		let mut groups: Vec<Vec<scanner::Region>> = Vec::new();
+		let mut previous_end: Option<usize> = None;
+		for region in regions.iter().filter(|r| r.kind == scanner::RegionKind::Str)
+		{
+			match (previous_end, groups.last_mut())
 			{
-				if char == '\''
-				{
-					number_of_singles += 1;
-					continue;
-				}
-				if char == '"'
+				(Some(end), Some(group)) if line[end..region.start].trim().is_empty() => group.push(*region),
+				_ => groups.push(vec![*region]),
+			}
+			previous_end = Some(region.end);
+		}
+
+		let mut replacements: Vec<(scanner::Region, String)> = Vec::new();
+		for group in groups
+		{
+			let converted: Vec<Option<String>> = group.iter().map(|r| convert_string_literal(&line[r.start..r.end], from, to)).collect();
+			if converted.iter().all(|c| c.is_some())
+			{
+				for (region, literal) in group.into_iter().zip(converted.into_iter().flatten())
 				{
-					number_of_doubles += 1;
+					if literal != line[region.start..region.end]
+					{
+						replacements.push((region, literal));
+					}
 				}
 			}
+		}
+
+		if replacements.is_empty()
+		{
+			return (line, false);
+		}
+
+		if self.config.verbose
+		{
+			println!("Found incorrect quotes - {}", line);
+		}
+
+		let mut fixed_line = line;
+		for (region, literal) in replacements.iter().rev()
+		{
+			fixed_line.replace_range(region.start..region.end, literal);
+		}
 
-			if number_of_singles > 0 && number_of_singles % 2 == 0 && number_of_doubles == 0
+		return (fixed_line, true);
+	}
+}
+
+fn detect_line_ending(content: &str) -> LineEnding
+{
+	match content.find(['\n', '\r'])
+	{
+		Some(index) if content[index..].starts_with("\r\n") => LineEnding::CrLf,
+		Some(index) if content[index..].starts_with('\r') => LineEnding::Cr,
+		_ => LineEnding::Lf,
+	}
+}
+
+/// Everything past this point works on `\n` line endings only
+fn normalize_line_endings(content: String) -> String
+{
+	if !content.contains('\r')
+	{
+		return content;
+	}
+	return content.replace("\r\n", "\n").replace('\r', "\n");
+}
+
+/// Converts a single line string literal from one quote to the other, or returns `None` when the literal can't be converted
+fn convert_string_literal(literal: &str, from: u8, to: u8) -> Option<String>
+{
+	let raw = literal.starts_with('r');
+	let delimited = if raw { &literal[1..] } else { literal };
+	let bytes = delimited.as_bytes();
+
+	if bytes.len() < 2 || bytes[bytes.len() - 1] != bytes[0]
+	{
+		return None;
+	}
+	if bytes[0] == to
+	{
+		return Some(literal.to_string());
+	}
+	if bytes[0] != from || delimited.starts_with(std::str::from_utf8(&[from, from, from]).unwrap_or_default())
+	{
+		return None;
+	}
+
+	let body = &bytes[1..bytes.len() - 1];
+	let mut converted: Vec<u8> = Vec::with_capacity(literal.len());
+	if raw
+	{
+		converted.push(b'r');
+	}
+	converted.push(to);
+
+	let mut i = 0;
+	while i < body.len()
+	{
+		let b = body[i];
+		if b == to
+		{
+			return None;
+		}
+		if !raw && b == b'\\' && i + 1 < body.len()
+		{
+			if body[i + 1] != from
 			{
-				return (line.replace("'", "\""), true);
+				converted.push(b);
 			}
+			converted.push(body[i + 1]);
+			i += 2;
+			continue;
 		}
-		return (line, false);
+		converted.push(b);
+		i += 1;
 	}
+
+	converted.push(to);
+
+	return String::from_utf8(converted).ok();
 }
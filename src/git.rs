@@ -0,0 +1,111 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Marks the hooks written by blink, so they can be replaced without `--force`
+const HOOK_MARKER: &str = "# Installed by blink";
+
+/// A file staged for the next commit, as stored in the index
+pub(crate) struct StagedFile
+{
+	pub(crate) path: PathBuf,
+	pub(crate) mode: String,
+	pub(crate) object: String,
+}
+
+pub(crate) fn repository_root(path: &Path) -> io::Result<PathBuf>
+{
+	let folder = if path.is_dir() { path } else { path.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new(".")) };
+	let output = git(folder, &["rev-parse", "--show-toplevel"], None)?;
+	return Ok(PathBuf::from(String::from_utf8_lossy(&output).trim_end()));
+}
+
+/// The files added, copied, modified or renamed in the index, with paths relative to the root of the repository
+pub(crate) fn staged_files(root: &Path) -> io::Result<Vec<StagedFile>>
+{
+	let output = git(root, &["diff", "--cached", "--name-only", "-z", "--diff-filter=ACMR"], None)?;
+	let mut files = Vec::new();
+
+	for path in output.split(|b| *b == 0).filter(|path| !path.is_empty())
+	{
+		let path = String::from_utf8_lossy(path).to_string();
+		// Gives `<mode> <object> <stage>\t<path>`
+		let entry = git(root, &["ls-files", "--stage", "-z", "--", &path], None)?;
+		let entry = String::from_utf8_lossy(&entry);
+		let mut fields = entry.split(['\t', ' ']);
+
+		if let (Some(mode), Some(object)) = (fields.next(), fields.next())
+		{
+			// Symbolic links and submodules aren't files to format
+			if mode == "100644" || mode == "100755"
+			{
+				files.push(StagedFile { path: PathBuf::from(path), mode: mode.to_string(), object: object.to_string() });
+			}
+		}
+	}
+
+	return Ok(files);
+}
+
+pub(crate) fn read_object(root: &Path, object: &str) -> io::Result<Vec<u8>>
+{
+	return git(root, &["cat-file", "blob", object], None);
+}
+
+/// Stores the content as the staged version of the file, leaving the working tree alone
+pub(crate) fn stage_content(root: &Path, file: &StagedFile, content: &str) -> io::Result<()>
+{
+	let object = git(root, &["hash-object", "-w", "--no-filters", "--stdin"], Some(content.as_bytes()))?;
+	let object = String::from_utf8_lossy(&object).trim_end().to_string();
+	let cache_info = format!("{},{},{}", file.mode, object, file.path.to_string_lossy());
+	git(root, &["update-index", "--cacheinfo", &cache_info], None)?;
+
+	return Ok(());
+}
+
+/// Writes a pre-commit hook running `blink --staged`, refusing to replace a hook blink didn't write unless forced
+pub(crate) fn install_hook(path: &Path, force: bool) -> io::Result<PathBuf>
+{
+	let root = repository_root(path)?;
+	let hooks = git(&root, &["rev-parse", "--git-path", "hooks"], None)?;
+	let hooks = root.join(String::from_utf8_lossy(&hooks).trim_end());
+	let hook = hooks.join("pre-commit");
+
+	if let Ok(existing) = fs::read_to_string(&hook)
+	{
+		if !existing.contains(HOOK_MARKER) && !force
+		{
+			return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("A pre-commit hook already exists at `{}`, use --force to replace it", hook.display())));
+		}
+	}
+
+	fs::create_dir_all(&hooks)?;
+	fs::write(&hook, format!("#!/bin/sh\n{}\nexec blink --staged\n", HOOK_MARKER))?;
+
+	#[cfg(unix)]
+	{
+		use std::os::unix::fs::PermissionsExt;
+		fs::set_permissions(&hook, fs::Permissions::from_mode(0o755))?;
+	}
+
+	return Ok(hook);
+}
+
+fn git(folder: &Path, args: &[&str], input: Option<&[u8]>) -> io::Result<Vec<u8>>
+{
+	let mut child = Command::new("git").arg("-C").arg(folder).args(args).stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() }).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+
+	if let (Some(input), Some(mut stdin)) = (input, child.stdin.take())
+	{
+		stdin.write_all(input)?;
+	}
+
+	let output = child.wait_with_output()?;
+	if !output.status.success()
+	{
+		return Err(io::Error::other(format!("git {} failed - {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim())));
+	}
+
+	return Ok(output.stdout);
+}
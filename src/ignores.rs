@@ -5,49 +5,57 @@ use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
 pub(crate) fn load_ignores(path: &PathBuf) -> HashSet<PathBuf>
+{
+	return find_blinkignore(path).map(|blinkignore_path| read_blinkignore(&blinkignore_path)).unwrap_or_default();
+}
+
+/// Lists the files ignored by a `.blinkignore`, with the folders in it expanded to the files they hold
+pub(crate) fn read_blinkignore(blinkignore_path: &Path) -> HashSet<PathBuf>
 {
 	let mut files: HashSet<PathBuf> = HashSet::new();
-	if let Some(blinkignore_path) = find_blinkignore(path)
+	if let Ok(file) = File::open(blinkignore_path)
 	{
-		if let Ok(file) = File::open(&blinkignore_path)
-		{
-			let reader = io::BufReader::new(file);
-			let blinkignore_dir = blinkignore_path.parent().unwrap_or(Path::new(""));
+		let reader = io::BufReader::new(file);
+		let blinkignore_dir = blinkignore_path.parent().unwrap_or(Path::new(""));
 
-			for res in reader.lines()
+		for line in reader.lines().map_while(Result::ok)
+		{
+			let ignored_path = blinkignore_dir.join(line.trim_end());
+			if ignored_path.is_dir()
 			{
-				if let Ok(line) = res
+				for entry in WalkDir::new(ignored_path)
 				{
-					let ignored_path = blinkignore_dir.join(line.trim_end());
-					if ignored_path.is_dir()
+					match entry
 					{
-						for entry in WalkDir::new(ignored_path)
+						Ok(entry) if entry.file_type().is_file() =>
 						{
-							let entry = entry.unwrap();
-							if entry.file_type().is_file()
-							{
-								files.insert(entry.path().to_path_buf());
-							}
+							files.insert(entry.path().to_path_buf());
+						}
+						Ok(_) =>
+						{}
+						Err(error) =>
+						{
+							eprintln!("Failed to list ignored files - {}", error);
 						}
-					}
-					else
-					{
-						files.insert(ignored_path);
 					}
 				}
 			}
+			else
+			{
+				files.insert(ignored_path);
+			}
 		}
 	}
 	return files;
 }
 
-fn find_blinkignore(starting_path: &Path) -> Option<PathBuf>
+pub(crate) fn find_blinkignore(starting_path: &Path) -> Option<PathBuf>
 {
 	if let Ok(start) = fs::canonicalize(starting_path)
 	{
 		let mut current_path = start.to_path_buf();
 
-		while current_path.as_os_str().len() > 0
+		while !current_path.as_os_str().is_empty()
 		{
 			let blinkignore_path = current_path.join(".blinkignore");
 			if fs::metadata(&blinkignore_path).is_ok()
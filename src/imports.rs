@@ -0,0 +1,237 @@
+use crate::formatter::Formatter;
+use crate::scanner::{self, depth_change, RegionKind};
+use crate::source::Source;
+
+struct Directive
+{
+	index: usize,
+	comments: Vec<String>,
+	lines: Vec<String>,
+	is_export: bool,
+	uri: String,
+}
+
+impl Directive
+{
+	fn group(&self) -> usize
+	{
+		let kind = if self.uri.starts_with("dart:")
+		{
+			0
+		}
+		else if self.uri.starts_with("package:")
+		{
+			1
+		}
+		else
+		{
+			2
+		};
+
+		if self.is_export
+		{
+			return kind + 3;
+		}
+		return kind;
+	}
+}
+
+impl Formatter
+{
+	pub fn organize_imports(&self, content: &String) -> (String, i32)
+	{
+		if !self.config.organize_imports
+		{
+			return (content.to_string(), 0);
+		}
+
+		let source = Source::new(content);
+		let lines = &source.lines;
+		let masked_lines = &source.masked_lines;
+
+		let start = match first_directive(lines, masked_lines)
+		{
+			Some(start) => start,
+			None => return (content.to_string(), 0),
+		};
+
+		let mut directives: Vec<Directive> = Vec::new();
+		let mut comments: Vec<String> = Vec::new();
+		let mut end = start;
+		let mut line_number = start;
+
+		while line_number < lines.len()
+		{
+			let masked = masked_lines[line_number].as_str();
+			if masked.trim().is_empty()
+			{
+				// Comments move along with the directive that follows them
+				if !lines[line_number].trim().is_empty()
+				{
+					comments.push(lines[line_number].trim_end().to_string());
+				}
+				line_number += 1;
+				continue;
+			}
+
+			if !is_directive(lines[line_number], masked)
+			{
+				break;
+			}
+
+			let first = line_number;
+			while !masked_lines[line_number].contains(';') && line_number + 1 < lines.len()
+			{
+				line_number += 1;
+			}
+
+			let directive_lines: Vec<String> = lines[first..=line_number].iter().map(|l| self.normalize_directive_quotes(l.trim_end())).collect();
+			let uri = directive_uri(&directive_lines.join("\n"));
+
+			directives.push(Directive { index: directives.len(), comments: std::mem::take(&mut comments), lines: directive_lines, is_export: masked.trim_start().starts_with("export"), uri });
+
+			line_number += 1;
+			end = line_number;
+		}
+
+		let original: Vec<String> = lines[start..end].iter().map(|l| l.to_string()).collect();
+
+		let mut organized: Vec<String> = Vec::new();
+		let mut incorrect_imports = 0;
+		let mut position = 0;
+
+		for group in 0..6
+		{
+			let mut members: Vec<&Directive> = directives.iter().filter(|d| d.group() == group).collect();
+			if members.is_empty()
+			{
+				continue;
+			}
+
+			members.sort_by(|a, b| a.uri.cmp(&b.uri).then(a.lines.cmp(&b.lines)));
+
+			if !organized.is_empty()
+			{
+				organized.push(String::from(""));
+			}
+
+			let mut previous: Option<&Directive> = None;
+			for directive in members
+			{
+				if let Some(p) = previous
+				{
+					if p.lines == directive.lines
+					{
+						if self.config.verbose
+						{
+							println!("Found duplicate import - {}", directive.lines.join(" "));
+						}
+						organized.extend(directive.comments.iter().cloned());
+						incorrect_imports += 1;
+						continue;
+					}
+				}
+
+				if directive.index != position
+				{
+					incorrect_imports += 1;
+				}
+				position += 1;
+
+				organized.extend(directive.comments.iter().cloned());
+				organized.extend(directive.lines.iter().cloned());
+				previous = Some(directive);
+			}
+		}
+
+		if organized == original
+		{
+			return (content.to_string(), 0);
+		}
+
+		if self.config.verbose
+		{
+			println!("Found unorganized imports");
+		}
+
+		if incorrect_imports == 0
+		{
+			incorrect_imports = 1;
+		}
+
+		let mut organized_content = String::from("");
+		for line in lines[..start].iter().chain(organized.iter().map(|l| l.as_str()).collect::<Vec<&str>>().iter()).chain(lines[end..].iter())
+		{
+			organized_content.push_str(line);
+			organized_content.push_str("\n");
+		}
+
+		return (organized_content, incorrect_imports);
+	}
+
+	fn normalize_directive_quotes(&self, line: &str) -> String
+	{
+		let (normalized, _) = if self.config.prefer_double_quotes { self.convert_quotes(line.to_string(), scanner::LineState::Code, b'\'', b'"') } else { self.convert_quotes(line.to_string(), scanner::LineState::Code, b'"', b'\'') };
+		return normalized;
+	}
+}
+
+/// Directives only come at the top of the file, after the library name and its annotations but before any declaration
+fn first_directive(lines: &[&str], masked_lines: &[String]) -> Option<usize>
+{
+	let mut depth = 0;
+
+	for (line_number, masked) in masked_lines.iter().enumerate()
+	{
+		let trimmed = masked.trim();
+		if trimmed.is_empty()
+		{
+			continue;
+		}
+		if depth == 0 && is_directive(lines[line_number], masked)
+		{
+			return Some(line_number);
+		}
+		if depth == 0 && !trimmed.starts_with("library") && !trimmed.starts_with('@')
+		{
+			return None;
+		}
+		depth += depth_change(masked);
+	}
+
+	return None;
+}
+
+/// Masked strings are all `_`, so `import'x';` masks like `import_data();` does, the quote is looked for in the line itself
+fn is_directive(line: &str, masked: &str) -> bool
+{
+	let trimmed = masked.trim_start();
+	let offset = masked.len() - trimmed.len();
+
+	for keyword in ["import", "export"]
+	{
+		if !trimmed.starts_with(keyword)
+		{
+			continue;
+		}
+
+		let after = offset + keyword.len();
+		if masked[after..].starts_with(char::is_whitespace) || line[after..].starts_with(['\'', '"'])
+		{
+			return true;
+		}
+	}
+
+	return false;
+}
+
+fn directive_uri(directive: &str) -> String
+{
+	let (regions, _) = scanner::scan_line(directive, scanner::LineState::Code);
+
+	match regions.iter().find(|r| r.kind == RegionKind::Str)
+	{
+		Some(region) => directive[region.start..region.end].trim_start_matches('r').trim_matches(|c| c == '\'' || c == '"').to_string(),
+		None => String::from(""),
+	}
+}
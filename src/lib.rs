@@ -0,0 +1,18 @@
+//! The formatting rules of blink, shared by the command line tool and the benchmarks
+
+mod arrows;
+mod blank_lines;
+mod chains;
+pub mod config;
+mod control_flow;
+mod curly_braces;
+pub mod formatter;
+mod imports;
+mod scanner;
+pub mod source;
+mod spacing;
+mod switches;
+mod trailing_commas;
+mod widget_trees;
+
+pub use formatter::Formatter;
@@ -1,25 +1,59 @@
 use std::{
-	collections::HashSet,
-	io::{self, Write},
-	path::PathBuf,
+	collections::{HashMap, HashSet},
+	io::{self, Read, Write},
+	path::{Path, PathBuf},
 };
 
-use clap::Parser;
-use ignores::load_ignores;
-
-mod config;
-mod formatter;
+use backup::Journal;
+use blink::{config, formatter};
+use cache::Cache;
+use clap::{Parser, Subcommand};
+use errors::{BlinkError, Report};
+use filters::Filters;
+use ignores::{find_blinkignore, load_ignores, read_blinkignore};
+
+mod backup;
+mod cache;
+mod errors;
+mod files;
+mod filters;
+mod git;
 mod ignores;
 // mod treesitter;
 
 fn main()
 {
 	let args = Arguments::parse();
-	let config = config::load(args.verbose, args.dry_run, &args.path.as_path());
-	// let config = config::load(args.verbose, args.dry_run, args.use_treesitter_to_format, &args.path.as_path());
+
+	match &args.command
+	{
+		Some(Command::Undo { path }) => std::process::exit(undo(path, args.verbose)),
+		Some(Command::Hook { action: HookCommand::Install { path, force } }) => std::process::exit(install_hook(path, *force)),
+		None =>
+		{}
+	}
+
+	let mut paths = args.paths;
+	if let Some(files_from) = &args.files_from
+	{
+		match files::read_file_list(files_from)
+		{
+			Ok(listed_paths) => paths.extend(listed_paths),
+			Err(error) =>
+			{
+				let error = BlinkError::from_io(files_from, "read the list of files", error);
+				eprintln!("Error: {}", error);
+				std::process::exit(error.exit_code());
+			}
+		}
+	}
+
+	// The config is loaded for each path, but checking the config or formatting standard input only needs one
+	let first_path = paths.first().cloned().unwrap_or(PathBuf::from("."));
 
 	if args.check_config
 	{
+		let config = config::load(args.verbose, args.dry_run, first_path.as_path());
 		println!("Config:");
 		println!("{}", config.display());
 		return;
@@ -27,7 +61,8 @@ fn main()
 
 	if args.standard_input
 	{
-		format_standard_input(config);
+		let config = config::load(args.verbose, args.dry_run, first_path.as_path());
+		std::process::exit(format_standard_input(config));
 	}
 	// else if args.tree_sitter_format
 	// {
@@ -41,30 +76,118 @@ fn main()
 	// }
 	else
 	{
-		format_files(config, &args.path, args.output);
-	}
-}
+		let res = if args.backup && !args.dry_run && args.output.is_none() && !args.staged && !paths.is_empty() { Journal::start(&first_path) } else { Ok(Journal::disabled()) };
 
-fn format_standard_input(config: config::Config)
-{
-	let formatter = formatter::Formatter { config };
-	let mut buffer = String::new();
-	let stdin = io::stdin();
-	let lines = stdin.lines();
-	for line_res in lines
-	{
-		match line_res
+		let journal = match res
 		{
-			Ok(line) =>
+			Ok(journal) => journal,
+			Err(error) =>
 			{
-				buffer.push_str(line.as_str());
-				buffer.push_str("\n");
+				let error = BlinkError::from_io(&first_path, "start the backup for", error);
+				eprintln!("Error: {}", error);
+				std::process::exit(error.exit_code());
 			}
+		};
+
+		let filters = match Filters::new(&args.include, &args.exclude, &args.extensions)
+		{
+			Ok(filters) => filters,
 			Err(error) =>
 			{
-				eprintln!("Failed to read line - {}", error);
+				eprintln!("Error: Invalid glob - {}", error);
+				std::process::exit(errors::EXIT_USAGE_ERROR);
 			}
+		};
+
+		let cache = if args.no_cache || args.output.is_some() { Cache::disabled() } else { Cache::new(args.dry_run) };
+		let configs = config::Configs::new(args.verbose, args.dry_run);
+		let mut run = Run { cache, configs, journal, report: Report::default(), copy_other_files: args.copy_other_files, filters };
+		let is_one_of_many = paths.len() > 1;
+
+		if args.staged
+		{
+			format_staged(args.verbose, args.dry_run, &first_path, &mut run);
+		}
+		else
+		{
+			// With many paths, like from `--files-from`, the ignores are loaded once per `.blinkignore`
+			let mut ignores: HashMap<Option<PathBuf>, HashSet<PathBuf>> = HashMap::new();
+
+			for path in &paths
+			{
+				let config = run.configs.get(path);
+				// let config = config::load(args.verbose, args.dry_run, args.use_treesitter_to_format, &args.path.as_path());
+
+				let ignored = ignores.entry(find_blinkignore(path)).or_insert_with_key(|blinkignore_path| blinkignore_path.as_deref().map(read_blinkignore).unwrap_or_default());
+				format_files(config, ignored, path, args.output.to_owned(), is_one_of_many, &mut run);
+			}
+
+			run.cache.save();
 		}
+
+		run.report.print_summary();
+		std::process::exit(run.report.exit_code());
+	}
+}
+
+/// State shared by all the files of a run
+struct Run
+{
+	cache: Cache,
+	configs: config::Configs,
+	journal: Journal,
+	report: Report,
+	copy_other_files: bool,
+	filters: Filters,
+}
+
+fn undo(path: &Path, verbose: bool) -> i32
+{
+	match backup::undo(path, verbose)
+	{
+		Ok(restored) =>
+		{
+			println!("Restored {} files", restored);
+			return 0;
+		}
+		Err(error) =>
+		{
+			let error = BlinkError::from_io(path, "undo the last run for", error);
+			eprintln!("Error: {}", error);
+			return error.exit_code();
+		}
+	}
+}
+
+fn install_hook(path: &Path, force: bool) -> i32
+{
+	match git::install_hook(path, force)
+	{
+		Ok(hook) =>
+		{
+			println!("Installed pre-commit hook at `{}`", hook.display());
+			return 0;
+		}
+		Err(error) =>
+		{
+			let error = BlinkError::from_io(path, "install the pre-commit hook for", error);
+			eprintln!("Error: {}", error);
+			return error.exit_code();
+		}
+	}
+}
+
+fn format_standard_input(config: config::Config) -> i32
+{
+	let formatter = formatter::Formatter { config };
+	let mut buffer = String::new();
+	let res = io::stdin().read_to_string(&mut buffer);
+
+	if let Err(error) = res
+	{
+		let error = BlinkError::from_io(Path::new("-"), "read standard input", error);
+		eprintln!("Error: {}", error);
+		return error.exit_code();
 	}
 
 	let result = formatter.format(buffer);
@@ -75,25 +198,51 @@ fn format_standard_input(config: config::Config)
 	match res
 	{
 		Ok(_) =>
-		{}
+		{
+			return 0;
+		}
 		Err(error) =>
 		{
 			eprintln!("Failed write to std out - {}", error);
+			return errors::EXIT_IO_ERROR;
 		}
 	}
 }
 
-fn format_files(config: config::Config, path: &PathBuf, output: Option<PathBuf>)
+fn format_files(config: config::Config, ignores: &HashSet<PathBuf>, path: &PathBuf, output: Option<PathBuf>, is_one_of_many: bool, run: &mut Run)
 {
-	let ignores = load_ignores(path);
-	format_file_or_files_in_folder(config, &ignores, path, output)
+	// A missing path is an error whatever its extension, it could be a folder as well as a file
+	if let Err(error) = std::fs::metadata(path)
+	{
+		run.report.add_error(BlinkError::from_io(path, "find", error));
+		return;
+	}
+
+	// A folder is mirrored into the output folder, a single file goes into it when it's a folder or replaces it otherwise.
+	// When given several paths, each one keeps its relative location within the output folder.
+	let target = match output
+	{
+		Some(output) if is_one_of_many => Some(output.join(path.components().filter(|c| matches!(c, std::path::Component::Normal(_))).collect::<PathBuf>())),
+		Some(output) if !path.is_dir() && (output.is_dir() || output.as_os_str().to_string_lossy().ends_with(std::path::MAIN_SEPARATOR)) => path.file_name().map(|file_name| output.join(file_name)),
+		output => output,
+	};
+
+	format_file_or_files_in_folder(config, ignores, path, target, run)
 }
 
-fn format_file_or_files_in_folder(config: config::Config, ignores: &HashSet<PathBuf>, path: &PathBuf, output: Option<PathBuf>)
+/// Formats the file or every file within the folder, writing them to the matching path within the target if given
+fn format_file_or_files_in_folder(config: config::Config, ignores: &HashSet<PathBuf>, path: &PathBuf, target: Option<PathBuf>, run: &mut Run)
 {
-	if path.is_dir()
+	if path.is_dir() && run.filters.is_excluded(path)
 	{
-		let res = std::fs::read_dir(&path);
+		if config.verbose
+		{
+			println!("Skipping excluded folder - {}", path.display());
+		}
+	}
+	else if path.is_dir()
+	{
+		let res = std::fs::read_dir(path);
 		match res
 		{
 			Ok(paths) =>
@@ -105,128 +254,163 @@ fn format_file_or_files_in_folder(config: config::Config, ignores: &HashSet<Path
 						Ok(entry) =>
 						{
 							let entry_path = entry.path();
-							if entry_path.is_dir()
-							{
-								if let Some(ref o) = output
-								{
-									if let Some(entry_file_name) = entry_path.file_name()
-									{
-										format_file_or_files_in_folder(config, ignores, &entry_path, Some(o.join(entry_file_name)));
-									}
-									else
-									{
-										println!("Failed to load filename for path {}", entry_path.display());
-									}
-								}
-								else
-								{
-									format_file_or_files_in_folder(config, ignores, &entry_path, output.to_owned());
-								}
-							}
-							else
-							{
-								format_file(config, &entry_path, ignores, output.to_owned());
-							}
+							let entry_target = target.as_ref().map(|t| t.join(entry.file_name()));
+							format_file_or_files_in_folder(config, ignores, &entry_path, entry_target, run);
 						}
 						Err(err) =>
 						{
-							println!("Failed to check item in folder - {}", err);
+							run.report.add_error(BlinkError::from_io(path, "check an item in folder", err));
 						}
 					}
 				}
 			}
 			Err(error) =>
 			{
-				println!("Failed to list contents of folder at path `{}`\nReason: {}", path.display(), error);
+				run.report.add_error(BlinkError::from_io(path, "list contents of folder", error));
 			}
 		}
 	}
 	else
 	{
-		format_file(config, &path, ignores, output.to_owned());
+		format_file(config, path, ignores, target, run);
 	}
 }
 
-fn format_file(config: config::Config, path: &PathBuf, ignores: &HashSet<PathBuf>, output_folder: Option<PathBuf>)
+fn format_file(config: config::Config, path: &PathBuf, ignores: &HashSet<PathBuf>, target: Option<PathBuf>, run: &mut Run)
 {
-	let output = output_folder.unwrap_or(path.to_path_buf());
-	if output.is_dir()
-	{
-		format_file_in_folder(config, path, ignores, &output);
-	}
-	else
+	if !run.filters.has_extension(path)
 	{
-		let parent = output.parent().unwrap().to_path_buf();
-
-		format_file_in_folder(config, path, ignores, &parent);
+		if config.verbose
+		{
+			println!("Skipping non dart file - {}", path.display());
+		}
+		copy_other_file(config, path, target, run);
+		return;
 	}
-}
 
-fn format_file_in_folder(config: config::Config, path: &PathBuf, ignores: &HashSet<PathBuf>, output_folder: &PathBuf)
-{
-	let canonical_path = std::fs::canonicalize(path).unwrap();
-	if path.extension().unwrap_or(std::ffi::OsStr::new("")) != "dart"
+	if run.filters.is_generated(path)
 	{
 		if config.verbose
 		{
-			println!("Skipping non dart file - {}", path.display());
+			println!("Skipping generated dart file - {}", path.display());
 		}
+		copy_other_file(config, path, target, run);
 		return;
 	}
 
-	if path.to_string_lossy().ends_with("g.dart")
+	if run.filters.is_excluded(path) || !run.filters.is_included(path)
 	{
 		if config.verbose
 		{
-			println!("Skipping generated dart file - {}", path.display());
+			println!("Skipping excluded file - {}", path.display());
 		}
+		copy_other_file(config, path, target, run);
 		return;
 	}
+
+	let canonical_path = match std::fs::canonicalize(path)
+	{
+		Ok(canonical_path) => canonical_path,
+		Err(error) =>
+		{
+			run.report.add_error(BlinkError::from_io(path, "find file", error));
+			return;
+		}
+	};
 	if ignores.contains(&canonical_path)
 	{
 		if config.verbose
 		{
 			println!("Skipping ignored file - {}", path.display());
 		}
+		copy_other_file(config, path, target, run);
 		return;
 	}
 
+	// Files in subfolders may have an `.editorconfig` of their own
+	let config = run.configs.get(path);
+
 	if config.verbose
 	{
 		println!("Checking `{}`...", path.display());
 	}
 
+	run.report.files += 1;
 	let res = std::fs::read_to_string(path);
 
 	match res
 	{
 		Ok(content) =>
 		{
-			let formatter = formatter::Formatter { config };
-			let result = formatter.format(content);
+			if run.cache.is_formatted(&canonical_path, &config, &content)
+			{
+				if config.verbose
+				{
+					println!("Skipping already formatted file - {}", path.display());
+				}
+				return;
+			}
 
-			let fixed_path = output_folder.join(path.file_name().unwrap());
+			let formatter = formatter::Formatter { config };
+			let result = formatter.format(content.clone());
 
 			if config.dryrun
 			{
 				println!("{}", result.content);
 
 				println!("Stats for {} (wrongs): ", path.display());
-				println!("  curlies: {} quotes: {} elses: {} indents: {} breaks: {}", result.incorrect_curly_braces, result.incorrect_quotes, result.incorrect_else_placements, result.incorrect_indentations, result.incorrect_break_placements);
+				println!(
+					"  curlies: {} quotes: {} elses: {} indents: {} breaks: {} switches: {} trailing commas: {} imports: {} control flow curlies: {} arrows: {} function bodies: {} widget trees: {} chains: {}",
+					result.incorrect_curly_braces,
+					result.incorrect_quotes,
+					result.incorrect_else_placements,
+					result.incorrect_indentations,
+					result.incorrect_break_placements,
+					result.incorrect_switch_indentations,
+					result.incorrect_trailing_commas,
+					result.incorrect_imports,
+					result.missing_control_flow_braces,
+					result.incorrect_arrow_indentations,
+					result.converted_function_bodies,
+					result.incorrect_widget_indentations,
+					result.incorrect_method_chains
+				);
+				println!("  spacing - operators: {} commas: {} call parens: {} keywords: {}", result.incorrect_operator_spacings, result.incorrect_comma_spacings, result.incorrect_call_paren_spacings, result.incorrect_keyword_spacings);
+
+				if result.content != content
+				{
+					run.report.unformatted += 1;
+				}
 			}
 			else
 			{
-				let wres = std::fs::write(fixed_path, result.content);
+				// The original is backed up before it gets overwritten, a file that can't be backed up is left alone
+				if result.content != content
+				{
+					if let Err(error) = run.journal.record(&canonical_path, &content, &result.content)
+					{
+						run.report.add_error(BlinkError::from_io(path, "back up file", error));
+						return;
+					}
+				}
+
+				let fixed_path = target.unwrap_or(path.to_path_buf());
+				let wres = files::create_parent_folders(&fixed_path).and_then(|_| files::write_atomically(&fixed_path, &result.content));
 
 				match wres
 				{
-					Ok(_) =>
-					{}
+					Ok(written) =>
+					{
+						if !written && config.verbose
+						{
+							println!("Leaving unchanged file alone - {}", path.display());
+						}
+						run.cache.mark_formatted(&canonical_path, &config, &result.content);
+					}
 
 					Err(err) =>
 					{
-						//println!("Error: Unable to write file `{}`\nReason: {}",fixed_path.display(),err);
-						println!("Error: Unable to write file {}", err);
+						run.report.add_error(BlinkError::from_io(&fixed_path, "write file", err));
 					}
 				}
 			}
@@ -234,17 +418,116 @@ fn format_file_in_folder(config: config::Config, path: &PathBuf, ignores: &HashS
 
 		Err(error) =>
 		{
-			println!("Error: Unable to read file `{}`\nReason: {}", path.display(), error);
+			run.report.add_error(BlinkError::from_io(path, "read file", error));
 		}
 	}
 }
 
+/// Formats the staged version of the files in the index and stages the result, the working tree only gets it for fully staged files
+fn format_staged(verbose: bool, dryrun: bool, path: &Path, run: &mut Run)
+{
+	let res = git::repository_root(path).and_then(|root| git::staged_files(&root).map(|staged_files| (root, staged_files)));
+
+	let (root, staged_files) = match res
+	{
+		Ok(res) => res,
+		Err(error) =>
+		{
+			run.report.add_error(BlinkError::from_io(path, "list the staged files of", error));
+			return;
+		}
+	};
+	let ignores = load_ignores(&root);
+
+	for file in staged_files
+	{
+		let file_path = root.join(&file.path);
+		if !run.filters.has_extension(&file.path) || run.filters.is_generated(&file.path) || run.filters.is_excluded(&file_path) || !run.filters.is_included(&file_path) || ignores.contains(&file_path)
+		{
+			continue;
+		}
+
+		if verbose
+		{
+			println!("Checking staged `{}`...", file.path.display());
+		}
+
+		run.report.files += 1;
+		let content = match git::read_object(&root, &file.object).map(String::from_utf8)
+		{
+			Ok(Ok(content)) => content,
+			Ok(Err(error)) =>
+			{
+				run.report.add_error(BlinkError::Parse { path: file_path, reason: error.to_string() });
+				continue;
+			}
+			Err(error) =>
+			{
+				run.report.add_error(BlinkError::from_io(&file_path, "read the staged version of", error));
+				continue;
+			}
+		};
+
+		let config = run.configs.get(&file_path);
+		let formatter = formatter::Formatter { config };
+		let result = formatter.format(content.clone());
+
+		if result.content == content
+		{
+			continue;
+		}
+
+		if dryrun
+		{
+			println!("Staged version of {} needs formatting", file.path.display());
+			run.report.unformatted += 1;
+			continue;
+		}
+
+		let is_fully_staged = std::fs::read(&file_path).is_ok_and(|working| working == content.as_bytes());
+
+		if let Err(error) = git::stage_content(&root, &file, &result.content)
+		{
+			run.report.add_error(BlinkError::from_io(&file_path, "stage the formatted version of", error));
+			continue;
+		}
+
+		if is_fully_staged
+		{
+			if let Err(error) = files::write_atomically(&file_path, &result.content)
+			{
+				run.report.add_error(BlinkError::from_io(&file_path, "write file", error));
+			}
+		}
+		else
+		{
+			println!("Formatted the staged version of {} only, its unstaged changes are left alone", file.path.display());
+		}
+	}
+}
+
+/// Copies a file that isn't formatted as is to the output, when asked to make the output a complete copy of the input
+fn copy_other_file(config: config::Config, path: &PathBuf, target: Option<PathBuf>, run: &mut Run)
+{
+	let target = match target
+	{
+		Some(target) if run.copy_other_files && !config.dryrun => target,
+		_ => return,
+	};
+
+	let res = files::create_parent_folders(&target).and_then(|_| std::fs::copy(path, &target));
+	if let Err(error) = res
+	{
+		run.report.add_error(BlinkError::from_io(&target, "copy file to", error));
+	}
+}
+
 #[derive(Parser)]
-#[clap(version, about, long_about = None)]
+#[clap(version, about, long_about = None, subcommand_negates_reqs = true)]
 /// A blazing fast code formatter for Dart
 struct Arguments
 {
-	#[clap(short, long)]
+	#[clap(short, long, global = true)]
 	/// Output more detailed extra information
 	verbose: bool,
 
@@ -272,11 +555,80 @@ struct Arguments
 	/// Check the config, don't make any changes
 	check_config: bool,
 
-	#[clap(parse(from_os_str))]
-	/// Path to input file or folder or working dir if using standard input
-	path: std::path::PathBuf,
+	#[clap(long = "no-cache")]
+	/// Format every file, even the ones already formatted by an earlier run
+	no_cache: bool,
+
+	#[clap(short = 'b', long)]
+	/// Keep the original content of every changed file, so `blink undo` can put it back
+	backup: bool,
+
+	#[clap(parse(from_os_str), required_unless_present_any = &["files-from", "staged"])]
+	/// Paths to input files or folders or working dir if using standard input
+	paths: Vec<std::path::PathBuf>,
+
+	#[clap(long = "files-from", parse(from_os_str))]
+	/// Also format the files listed in this file, or standard input if `-`, one per line or separated by NUL characters
+	files_from: Option<std::path::PathBuf>,
 
 	#[clap(short, long, parse(from_os_str))]
 	/// Path to output destination, overwrites files if omitted
 	output: Option<std::path::PathBuf>,
+
+	#[clap(long = "copy-other-files", requires = "output")]
+	/// Copy the files that aren't formatted to the output as well, making it a complete copy of the input
+	copy_other_files: bool,
+
+	#[clap(long, multiple_occurrences = true)]
+	/// Only format files matching this glob, relative to the project root, can be given several times
+	include: Vec<String>,
+
+	#[clap(long, multiple_occurrences = true)]
+	/// Skip files and folders matching this glob, relative to the project root, can be given several times
+	exclude: Vec<String>,
+
+	#[clap(long, multiple_occurrences = true, use_value_delimiter = true)]
+	/// Format files with these extensions as well as `.dart` ones, separated by commas
+	extensions: Vec<String>,
+
+	#[clap(long)]
+	/// Format the staged version of the files in the git index and stage the result, as a pre-commit hook would
+	staged: bool,
+
+	#[clap(subcommand)]
+	command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command
+{
+	/// Put back the files changed by the last run made with `--backup`
+	Undo
+	{
+		#[clap(parse(from_os_str), default_value = ".")]
+		/// Path within the project to undo the last run of
+		path: std::path::PathBuf,
+	},
+	/// Manage the git pre-commit hook
+	Hook
+	{
+		#[clap(subcommand)]
+		action: HookCommand,
+	},
+}
+
+#[derive(Subcommand)]
+enum HookCommand
+{
+	/// Install a pre-commit hook formatting the staged files with `blink --staged`
+	Install
+	{
+		#[clap(parse(from_os_str), default_value = ".")]
+		/// Path within the git repository to install the hook for
+		path: std::path::PathBuf,
+
+		#[clap(short, long)]
+		/// Replace an existing pre-commit hook not installed by blink
+		force: bool,
+	},
 }
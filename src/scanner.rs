@@ -0,0 +1,304 @@
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum RegionKind
+{
+	Code,
+	Str,
+	Comment,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Region
+{
+	pub(crate) kind: RegionKind,
+	pub(crate) start: usize,
+	pub(crate) end: usize,
+}
+
+/// What the scanner is in the middle of when a line ends
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum LineState
+{
+	#[default]
+	Code,
+	BlockComment(usize),
+	MultiLineString(u8, bool),
+}
+
+pub(crate) fn scan_line(line: &str, state: LineState) -> (Vec<Region>, LineState)
+{
+	let bytes = line.as_bytes();
+	let len = bytes.len();
+	let mut regions: Vec<Region> = Vec::new();
+	let mut i = 0;
+	let mut state = state;
+
+	match state
+	{
+		LineState::Code =>
+		{}
+		LineState::BlockComment(depth) =>
+		{
+			let (end, depth) = skip_block_comment(bytes, 0, depth);
+			regions.push(Region { kind: RegionKind::Comment, start: 0, end });
+			if depth > 0
+			{
+				return (regions, LineState::BlockComment(depth));
+			}
+			state = LineState::Code;
+			i = end;
+		}
+		LineState::MultiLineString(quote, raw) => match find_string_end(bytes, 0, quote, true, raw)
+		{
+			Some(end) =>
+			{
+				regions.push(Region { kind: RegionKind::Str, start: 0, end });
+				state = LineState::Code;
+				i = end;
+			}
+			None =>
+			{
+				regions.push(Region { kind: RegionKind::Str, start: 0, end: len });
+				return (regions, state);
+			}
+		},
+	}
+
+	let mut code_start = i;
+
+	while i < len
+	{
+		let b = bytes[i];
+		if b == b'/' && i + 1 < len && bytes[i + 1] == b'/'
+		{
+			push_code(&mut regions, code_start, i);
+			regions.push(Region { kind: RegionKind::Comment, start: i, end: len });
+			return (regions, state);
+		}
+		if b == b'/' && i + 1 < len && bytes[i + 1] == b'*'
+		{
+			push_code(&mut regions, code_start, i);
+			let (end, depth) = skip_block_comment(bytes, i + 2, 1);
+			regions.push(Region { kind: RegionKind::Comment, start: i, end });
+			if depth > 0
+			{
+				return (regions, LineState::BlockComment(depth));
+			}
+			i = end;
+			code_start = i;
+			continue;
+		}
+		if b == b'\'' || b == b'"'
+		{
+			let raw = i > 0 && bytes[i - 1] == b'r' && (i < 2 || !is_identifier_byte(bytes[i - 2]));
+			let start = if raw { i - 1 } else { i };
+			push_code(&mut regions, code_start, start);
+
+			let triple = i + 2 < len && bytes[i + 1] == b && bytes[i + 2] == b;
+			let body = if triple { i + 3 } else { i + 1 };
+
+			match find_string_end(bytes, body, b, triple, raw)
+			{
+				Some(end) =>
+				{
+					regions.push(Region { kind: RegionKind::Str, start, end });
+					i = end;
+				}
+				None =>
+				{
+					regions.push(Region { kind: RegionKind::Str, start, end: len });
+					if triple
+					{
+						return (regions, LineState::MultiLineString(b, raw));
+					}
+					return (regions, state);
+				}
+			}
+			code_start = i;
+			continue;
+		}
+		i += 1;
+	}
+
+	push_code(&mut regions, code_start, len);
+
+	return (regions, state);
+}
+
+/// Returns the line with string literals replaced by `_` and comments by spaces, keeping every byte offset intact
+pub(crate) fn mask_line(line: &str, state: LineState) -> (String, LineState)
+{
+	let (regions, next_state) = scan_line(line, state);
+	let mut masked = line.as_bytes().to_vec();
+
+	for region in regions
+	{
+		let filler = match region.kind
+		{
+			RegionKind::Code => continue,
+			RegionKind::Str => b'_',
+			RegionKind::Comment => b' ',
+		};
+		for byte in &mut masked[region.start..region.end]
+		{
+			*byte = filler;
+		}
+	}
+
+	return (String::from_utf8(masked).unwrap_or_default(), next_state);
+}
+
+/// Same as `mask_line` for a whole file, newlines are kept where they are
+pub(crate) fn mask_content(content: &str) -> String
+{
+	let mut masked = String::with_capacity(content.len());
+	let mut state = LineState::Code;
+
+	for line in content.split_inclusive('\n')
+	{
+		let body = line.strip_suffix('\n').unwrap_or(line);
+		let (mline, next_state) = mask_line(body, state);
+		masked.push_str(&mline);
+		if body.len() != line.len()
+		{
+			masked.push('\n');
+		}
+		state = next_state;
+	}
+
+	return masked;
+}
+
+/// How many more brackets the masked code opens than it closes
+pub(crate) fn depth_change(masked: &str) -> isize
+{
+	let mut change = 0;
+
+	for b in masked.bytes()
+	{
+		match b
+		{
+			b'{' | b'(' | b'[' => change += 1,
+			b'}' | b')' | b']' => change -= 1,
+			_ =>
+			{}
+		}
+	}
+
+	return change;
+}
+
+pub(crate) fn starts_with_word(masked: &str, word: &str) -> bool
+{
+	return masked.starts_with(word) && !masked[word.len()..].starts_with(|c: char| c.is_alphanumeric() || c == '_');
+}
+
+pub(crate) fn is_identifier_byte(b: u8) -> bool
+{
+	return b.is_ascii_alphanumeric() || b == b'_' || b == b'$';
+}
+
+fn push_code(regions: &mut Vec<Region>, start: usize, end: usize)
+{
+	if end > start
+	{
+		regions.push(Region { kind: RegionKind::Code, start, end });
+	}
+}
+
+fn skip_block_comment(bytes: &[u8], from: usize, depth: usize) -> (usize, usize)
+{
+	let mut depth = depth;
+	let mut i = from;
+
+	while i < bytes.len()
+	{
+		if bytes[i] == b'/' && i + 1 < bytes.len() && bytes[i + 1] == b'*'
+		{
+			depth += 1;
+			i += 2;
+			continue;
+		}
+		if bytes[i] == b'*' && i + 1 < bytes.len() && bytes[i + 1] == b'/'
+		{
+			depth -= 1;
+			i += 2;
+			if depth == 0
+			{
+				return (i, 0);
+			}
+			continue;
+		}
+		i += 1;
+	}
+
+	return (bytes.len(), depth);
+}
+
+fn find_string_end(bytes: &[u8], from: usize, quote: u8, triple: bool, raw: bool) -> Option<usize>
+{
+	let mut i = from;
+
+	while i < bytes.len()
+	{
+		let b = bytes[i];
+		if !raw && b == b'\\'
+		{
+			i += 2;
+			continue;
+		}
+		if !raw && b == b'$' && i + 1 < bytes.len() && bytes[i + 1] == b'{'
+		{
+			i = skip_interpolation(bytes, i + 2);
+			continue;
+		}
+		if b == quote
+		{
+			if !triple
+			{
+				return Some(i + 1);
+			}
+			if i + 2 < bytes.len() && bytes[i + 1] == quote && bytes[i + 2] == quote
+			{
+				return Some(i + 3);
+			}
+		}
+		i += 1;
+	}
+
+	return None;
+}
+
+fn skip_interpolation(bytes: &[u8], from: usize) -> usize
+{
+	let mut depth = 1;
+	let mut i = from;
+
+	while i < bytes.len()
+	{
+		let b = bytes[i];
+		if b == b'\'' || b == b'"'
+		{
+			match find_string_end(bytes, i + 1, b, false, false)
+			{
+				Some(end) => i = end,
+				None => return bytes.len(),
+			}
+			continue;
+		}
+		if b == b'{'
+		{
+			depth += 1;
+		}
+		else if b == b'}'
+		{
+			depth -= 1;
+			if depth == 0
+			{
+				return i + 1;
+			}
+		}
+		i += 1;
+	}
+
+	return bytes.len();
+}
@@ -0,0 +1,83 @@
+use crate::scanner;
+
+/// Lines no rule may touch, like the ones within triple quoted strings or holding only a comment, one bit per line
+pub struct ProtectedLines
+{
+	bits: Vec<u64>,
+}
+
+impl ProtectedLines
+{
+	fn new(line_count: usize) -> ProtectedLines
+	{
+		return ProtectedLines { bits: vec![0; line_count.div_ceil(64)] };
+	}
+
+	fn insert(&mut self, line_number: usize)
+	{
+		self.bits[line_number / 64] |= 1 << (line_number % 64);
+	}
+
+	pub fn contains(&self, line_number: usize) -> bool
+	{
+		return self.bits.get(line_number / 64).is_some_and(|bits| bits & (1 << (line_number % 64)) != 0);
+	}
+}
+
+/// A file split into lines along with their masked code, scanner state and protection, shared by the rules working on whole files
+pub struct Source<'a>
+{
+	pub lines: Vec<&'a str>,
+	pub masked_lines: Vec<String>,
+	/// The scanner state at the start of each line
+	pub states: Vec<scanner::LineState>,
+	pub protected: ProtectedLines,
+}
+
+impl<'a> Source<'a>
+{
+	pub fn new(content: &'a str) -> Source<'a>
+	{
+		let lines: Vec<&str> = content.lines().collect();
+
+		let mut masked_lines: Vec<String> = Vec::with_capacity(lines.len());
+		let mut states: Vec<scanner::LineState> = Vec::with_capacity(lines.len());
+		let mut state = scanner::LineState::Code;
+
+		for line in &lines
+		{
+			let (masked, next_state) = scanner::mask_line(line, state);
+			masked_lines.push(masked);
+			states.push(state);
+			state = next_state;
+		}
+
+		let protected = protected_lines(&lines, &states);
+
+		return Source { lines, masked_lines, states, protected };
+	}
+
+	pub fn owned_lines(&self) -> Vec<String>
+	{
+		return self.lines.iter().map(|line| line.to_string()).collect();
+	}
+}
+
+/// Lines starting or ending within a multi-line string, along with the ones holding only a comment
+fn protected_lines(lines: &[&str], states: &[scanner::LineState]) -> ProtectedLines
+{
+	let mut protected = ProtectedLines::new(lines.len());
+
+	for (line_number, line) in lines.iter().enumerate()
+	{
+		let starts_inside_string = matches!(states[line_number], scanner::LineState::MultiLineString(..));
+		let ends_inside_string = matches!(states.get(line_number + 1), Some(scanner::LineState::MultiLineString(..)));
+
+		if starts_inside_string || ends_inside_string || line.trim().starts_with("//")
+		{
+			protected.insert(line_number);
+		}
+	}
+
+	return protected;
+}
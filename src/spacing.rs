@@ -0,0 +1,334 @@
+use crate::formatter::Formatter;
+use crate::scanner::{self, is_identifier_byte};
+
+#[derive(Default)]
+pub struct SpacingChanges
+{
+	pub(crate) operators: i32,
+	pub(crate) commas: i32,
+	pub(crate) call_parens: i32,
+	pub(crate) keywords: i32,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum SpacingRule
+{
+	Operator,
+	Comma,
+	CallParen,
+	Keyword,
+}
+
+struct Token
+{
+	start: usize,
+	end: usize,
+	is_binary: bool,
+}
+
+const OPERATORS: [&str; 38] = [">>>=", "...?", "??=", "~/=", ">>=", "<<=", "...", "?..", "==", "!=", "<=", ">=", "=>", "&&", "||", "??", "?.", "++", "--", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "~/", "<<", "..", "=", "+", "-", "*", "/", "%", "&", "|"];
+const ALWAYS_BINARY: [&str; 26] = ["??=", "~/=", ">>=", "<<=", ">>>=", "==", "!=", "<=", ">=", "=>", "&&", "||", "??", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "~/", "<<", "=", "/", "%"];
+const CONTROL_KEYWORDS: [&str; 5] = ["if", "for", "while", "switch", "catch"];
+const NON_CALL_KEYWORDS: [&str; 23] = ["if", "for", "while", "switch", "catch", "return", "await", "yield", "throw", "in", "is", "as", "case", "when", "else", "do", "new", "const", "final", "var", "async", "sync", "on"];
+const NON_OPERAND_KEYWORDS: [&str; 11] = ["return", "case", "yield", "await", "throw", "in", "else", "when", "is", "as", "async"];
+
+impl Formatter
+{
+	pub fn fix_incorrect_spacing(&self, line: String, state: scanner::LineState) -> (String, SpacingChanges)
+	{
+		let mut changes = SpacingChanges::default();
+
+		if !self.config.normalize_spacing
+		{
+			return (line, changes);
+		}
+
+		// The curly brace fixer may already have split the line in two
+		let mut fixed_lines: Vec<String> = Vec::new();
+		let mut part_state = state;
+		for part in line.split('\n')
+		{
+			fixed_lines.push(self.fix_incorrect_spacing_in_line(part, part_state, &mut changes));
+			part_state = scanner::LineState::Code;
+		}
+
+		return (fixed_lines.join("\n"), changes);
+	}
+
+	fn fix_incorrect_spacing_in_line(&self, line: &str, state: scanner::LineState, changes: &mut SpacingChanges) -> String
+	{
+		let delta = line.len() - line.trim_start().len();
+		let body = &line[delta..];
+		let (masked, _) = scanner::mask_line(body, state);
+		let code_end = masked.trim_end().len();
+		let code = &masked.as_bytes()[..code_end];
+
+		let tokens = tokenize(code);
+		if tokens.len() < 2
+		{
+			return line.to_string();
+		}
+
+		let mut fixed = String::from(&line[..delta]);
+		fixed.push_str(&body[..tokens[0].start]);
+		let mut changed = false;
+
+		for (k, token) in tokens.iter().enumerate()
+		{
+			if k > 0
+			{
+				let gap = &body[tokens[k - 1].end..token.start];
+				match desired_gap(code, &tokens, k)
+				{
+					Some((desired, rule)) if desired != gap && gap.trim().is_empty() =>
+					{
+						match rule
+						{
+							SpacingRule::Operator => changes.operators += 1,
+							SpacingRule::Comma => changes.commas += 1,
+							SpacingRule::CallParen => changes.call_parens += 1,
+							SpacingRule::Keyword => changes.keywords += 1,
+						}
+						fixed.push_str(desired);
+						changed = true;
+					}
+					_ => fixed.push_str(gap),
+				}
+			}
+			fixed.push_str(&body[token.start..token.end]);
+		}
+
+		fixed.push_str(&body[code_end..]);
+
+		if changed && self.config.verbose
+		{
+			println!("Found incorrect spacing - {}", line);
+		}
+
+		return fixed;
+	}
+}
+
+fn desired_gap(code: &[u8], tokens: &[Token], k: usize) -> Option<(&'static str, SpacingRule)>
+{
+	let previous = &tokens[k - 1];
+	let token = &tokens[k];
+	let previous_text = text(code, previous);
+	let token_text = text(code, token);
+
+	if token_text == ","
+	{
+		return Some(("", SpacingRule::Comma));
+	}
+	if previous_text == "," || previous_text == ":"
+	{
+		if [")", "]", "}", ">", ","].contains(&token_text)
+		{
+			return None;
+		}
+		// Pattern shorthand like `Point(:var x, :var y)`
+		let is_pattern_shorthand = previous_text == ":" && k > 1 && ["(", ","].contains(&text(code, &tokens[k - 2]));
+		if is_pattern_shorthand || (previous_text == ":" && is_conditional_colon(code, tokens, k - 1))
+		{
+			return None;
+		}
+		return Some((" ", SpacingRule::Comma));
+	}
+
+	if token_text == "("
+	{
+		if CONTROL_KEYWORDS.contains(&previous_text)
+		{
+			return Some((" ", SpacingRule::Keyword));
+		}
+		let is_name = previous_text.as_bytes().iter().all(|b| is_identifier_byte(*b) || *b >= 0x80) && !previous_text.starts_with(|c: char| c.is_ascii_digit());
+		if is_name && !NON_CALL_KEYWORDS.contains(&previous_text)
+		{
+			return Some(("", SpacingRule::CallParen));
+		}
+		return None;
+	}
+
+	if token.is_binary || previous.is_binary
+	{
+		return Some((" ", SpacingRule::Operator));
+	}
+
+	return None;
+}
+
+/// The colon of `c ? a:b` is left alone like its question mark, only the ones of map entries and named arguments get a space
+fn is_conditional_colon(code: &[u8], tokens: &[Token], k: usize) -> bool
+{
+	let mut depth = 0;
+	let mut colons = 0;
+
+	for token in tokens[..k].iter().rev()
+	{
+		match text(code, token)
+		{
+			")" | "]" | "}" => depth += 1,
+			"(" | "[" | "{" =>
+			{
+				if depth == 0
+				{
+					return false;
+				}
+				depth -= 1;
+			}
+			":" if depth == 0 => colons += 1,
+			"?" if depth == 0 =>
+			{
+				if colons == 0
+				{
+					return true;
+				}
+				colons -= 1;
+			}
+			_ =>
+			{}
+		}
+	}
+
+	return false;
+}
+
+fn text<'a>(code: &'a [u8], token: &Token) -> &'a str
+{
+	return std::str::from_utf8(&code[token.start..token.end]).unwrap_or("");
+}
+
+fn is_word_byte(b: u8) -> bool
+{
+	return is_identifier_byte(b) || b >= 0x80;
+}
+
+fn tokenize(code: &[u8]) -> Vec<Token>
+{
+	let mut tokens: Vec<Token> = Vec::new();
+	let mut i = 0;
+
+	while i < code.len()
+	{
+		let b = code[i];
+		if b.is_ascii_whitespace()
+		{
+			i += 1;
+			continue;
+		}
+
+		let start = i;
+		if b.is_ascii_digit()
+		{
+			while i < code.len() && (is_word_byte(code[i]) || (code[i] == b'.' && i + 1 < code.len() && code[i + 1].is_ascii_digit()) || ((code[i] == b'-' || code[i] == b'+') && (code[i - 1] == b'e' || code[i - 1] == b'E') && !code[start..i].starts_with(b"0x")))
+			{
+				i += 1;
+			}
+		}
+		else if is_word_byte(b)
+		{
+			while i < code.len() && is_word_byte(code[i])
+			{
+				i += 1;
+			}
+		}
+		else
+		{
+			let operator = OPERATORS.iter().find(|o| code[i..].starts_with(o.as_bytes()));
+			i += operator.map(|o| o.len()).unwrap_or(1);
+		}
+
+		tokens.push(Token { start, end: i, is_binary: false });
+	}
+
+	classify_operators(code, &mut tokens);
+
+	return tokens;
+}
+
+fn classify_operators(code: &[u8], tokens: &mut [Token])
+{
+	let mut generic_closers: Vec<usize> = Vec::new();
+
+	for k in 0..tokens.len()
+	{
+		let token_text = text(code, &tokens[k]).to_string();
+		let previous_is_operand = k > 0 && is_operand(text(code, &tokens[k - 1]));
+		let next_is_adjacent = k + 1 < tokens.len() && tokens[k + 1].start == tokens[k].end;
+
+		tokens[k].is_binary = match token_text.as_str()
+		{
+			"<" => match generic_closer(code, tokens, k)
+			{
+				Some(closer) =>
+				{
+					generic_closers.push(closer);
+					false
+				}
+				None => previous_is_operand,
+			},
+			">" =>
+			{
+				let next_is_angle = next_is_adjacent && text(code, &tokens[k + 1]) == ">";
+				let previous_is_angle = k > 0 && tokens[k - 1].end == tokens[k].start && text(code, &tokens[k - 1]) == ">";
+				!generic_closers.contains(&k) && previous_is_operand && !next_is_angle && !previous_is_angle
+			}
+			"+" | "-" | "*" | "&" | "|" => previous_is_operand && !(token_text == "*" && ["yield", "async", "sync"].contains(&text(code, &tokens[k - 1]))),
+			t => ALWAYS_BINARY.contains(&t) && k > 0,
+		};
+	}
+}
+
+fn is_operand(token_text: &str) -> bool
+{
+	if token_text == ")" || token_text == "]"
+	{
+		return true;
+	}
+	return token_text.as_bytes().first().is_some_and(|b| is_word_byte(*b)) && !NON_OPERAND_KEYWORDS.contains(&token_text);
+}
+
+/// Finds the `>` closing a type argument list opened at token `k`, if the tokens in between can form one
+fn generic_closer(code: &[u8], tokens: &[Token], k: usize) -> Option<usize>
+{
+	let mut depth = 0;
+	let mut parens = 0;
+
+	for (j, token) in tokens.iter().enumerate().skip(k)
+	{
+		let token_text = text(code, token);
+		match token_text
+		{
+			"<" => depth += 1,
+			">" =>
+			{
+				depth -= 1;
+				if depth == 0
+				{
+					return Some(j);
+				}
+			}
+			"(" => parens += 1,
+			")" =>
+			{
+				parens -= 1;
+				if parens < 0
+				{
+					return None;
+				}
+			}
+			"," | "." | "?" =>
+			{}
+			t =>
+			{
+				let is_type_name = t.as_bytes().iter().all(|b| is_word_byte(*b)) && !t.starts_with(|c: char| c.is_ascii_digit());
+				if !is_type_name
+				{
+					return None;
+				}
+			}
+		}
+	}
+
+	return None;
+}
@@ -0,0 +1,219 @@
+use crate::formatter::Formatter;
+use crate::scanner::{depth_change, starts_with_word};
+use crate::source::{ProtectedLines, Source};
+use regex::Regex;
+use std::sync::OnceLock;
+
+static SWITCH_REGEX: OnceLock<Regex> = OnceLock::new();
+
+struct SwitchBlock
+{
+	start_line: usize,
+	is_expression: bool,
+}
+
+impl Formatter
+{
+	pub fn correct_switch_indentations(&self, content: &String) -> (String, i32)
+	{
+		let switch_regex = SWITCH_REGEX.get_or_init(|| Regex::new(r"\bswitch\s*\(").unwrap());
+
+		let source = Source::new(content);
+		let mut lines = source.owned_lines();
+		let masked_lines = &source.masked_lines;
+
+		let mut incorrect_switch_indentations = 0;
+
+		for line_number in 0..lines.len()
+		{
+			if source.protected.contains(line_number)
+			{
+				continue;
+			}
+
+			// Outer switches are handled first, so nested ones are indented relative to where their outer switch put them
+			if let Some(found) = switch_regex.find(&masked_lines[line_number])
+			{
+				let is_expression = !masked_lines[line_number][..found.start()].trim().is_empty();
+				let switch = SwitchBlock { start_line: line_number, is_expression };
+				incorrect_switch_indentations += self.correct_switch_block(&mut lines, masked_lines, &source.protected, &switch, found.end());
+			}
+		}
+
+		let mut cleaned_content = String::from("");
+
+		for line in lines
+		{
+			cleaned_content.push_str(line.as_str());
+			cleaned_content.push_str("\n");
+		}
+
+		return (cleaned_content, incorrect_switch_indentations);
+	}
+
+	fn correct_switch_block(&self, lines: &mut [String], masked_lines: &[String], protected_lines: &ProtectedLines, switch: &SwitchBlock, after_paren: usize) -> i32
+	{
+		let unit = self.config.indentation.size.max(1);
+		let base = self.indentation_width(&lines[switch.start_line]);
+
+		// Find the curly brace opening the switch body, right after the parenthesized value
+		let mut line_number = switch.start_line;
+		let mut position = after_paren;
+		let mut parens = 1;
+		let mut found_body = false;
+
+		while line_number < lines.len() && !found_body
+		{
+			let masked = masked_lines[line_number].as_bytes();
+			while position < masked.len()
+			{
+				match masked[position]
+				{
+					b'(' => parens += 1,
+					b')' => parens -= 1,
+					b'{' if parens == 0 =>
+					{
+						found_body = true;
+						break;
+					}
+					_ =>
+					{}
+				}
+				position += 1;
+			}
+			if !found_body
+			{
+				line_number += 1;
+				position = 0;
+			}
+		}
+
+		if !found_body
+		{
+			return 0;
+		}
+
+		let mut changed = 0;
+
+		if line_number != switch.start_line
+		{
+			changed += self.indent_switch_line(lines, line_number, base);
+		}
+
+		let mut depth = depth_change(&masked_lines[line_number][position..]);
+
+		// A switch like `return switch (v) { 1 => 2, _ => 3 };` has no lines of its own to indent
+		if depth <= 0
+		{
+			return changed;
+		}
+
+		let mut delta: isize = 0;
+		let mut is_arm_start = true;
+		let mut is_braceless_body = false;
+		let mut previous_target = base + unit;
+		let mut previous_is_label = false;
+		// Where the lines holding still open curly braces were put, so the closing ones line up with them
+		let mut opener_targets: Vec<usize> = Vec::new();
+
+		for current in line_number + 1..lines.len()
+		{
+			let masked = masked_lines[current].trim();
+			let depth_at_start = depth;
+			depth += depth_change(masked);
+
+			if masked.is_empty() || protected_lines.contains(current)
+			{
+				continue;
+			}
+
+			let leading_closers = masked.bytes().take_while(|b| *b == b'}' || *b == b')' || *b == b']').count() as isize;
+			let level = depth_at_start - leading_closers;
+
+			if level <= 0
+			{
+				changed += self.indent_switch_line(lines, current, base);
+				break;
+			}
+
+			if level > 1 || (is_braceless_body && !masked.starts_with('{'))
+			{
+				let width = (self.indentation_width(&lines[current]) as isize + delta).max(0) as usize;
+				changed += self.indent_switch_line(lines, current, width);
+				is_braceless_body = level == 1 && is_braceless_header(masked);
+				continue;
+			}
+
+			let target = if switch.is_expression
+			{
+				if is_arm_start || leading_closers > 0
+				{
+					base + unit
+				}
+				else
+				{
+					base + 2 * unit
+				}
+			}
+			else if masked.starts_with('{') && !previous_is_label
+			{
+				previous_target
+			}
+			else if masked.starts_with('}') && !opener_targets.is_empty()
+			{
+				opener_targets.pop().unwrap_or(base + unit)
+			}
+			else if starts_with_word(masked, "case") || starts_with_word(masked, "default") || masked.starts_with('}') || masked.starts_with('{') || masked.starts_with("break;")
+			{
+				base + unit
+			}
+			else
+			{
+				base + 2 * unit
+			};
+
+			delta = target as isize - self.indentation_width(&lines[current]) as isize;
+			changed += self.indent_switch_line(lines, current, target);
+
+			if !switch.is_expression && masked.ends_with('{')
+			{
+				opener_targets.push(target);
+			}
+			previous_target = target;
+			previous_is_label = masked.ends_with(':');
+
+			is_arm_start = depth == 1 && masked.ends_with(',');
+			is_braceless_body = !switch.is_expression && is_braceless_header(masked);
+		}
+
+		return changed;
+	}
+
+	fn indent_switch_line(&self, lines: &mut [String], line_number: usize, width: usize) -> i32
+	{
+		let reindented = self.reindent(&lines[line_number], width);
+		if reindented == lines[line_number]
+		{
+			return 0;
+		}
+
+		if self.config.verbose
+		{
+			println!("Found incorrect switch indentation - {}", lines[line_number]);
+		}
+
+		lines[line_number] = reindented;
+		return 1;
+	}
+}
+
+/// Whether the next line is the body of an `if`, `for`, `while` or `else` written without curly braces
+fn is_braceless_header(masked: &str) -> bool
+{
+	if starts_with_word(masked, "else") && !masked.ends_with('{') && !masked.ends_with(';')
+	{
+		return true;
+	}
+	let is_control = ["if", "for", "while"].iter().any(|k| starts_with_word(masked, k)) || masked.starts_with("} else if") || masked.starts_with("else if");
+	return is_control && masked.ends_with(')');
+}
@@ -0,0 +1,246 @@
+use crate::config::TrailingCommas;
+use crate::formatter::Formatter;
+use crate::scanner::{self, is_identifier_byte};
+
+struct Opener
+{
+	position: usize,
+	qualifies: bool,
+	call: bool,
+	commas: usize,
+	semicolons: usize,
+}
+
+enum CommaEdit
+{
+	Insert(usize),
+	Remove(usize, usize),
+}
+
+const NON_CALL_KEYWORDS: [&str; 14] = ["if", "for", "while", "switch", "catch", "return", "await", "yield", "in", "case", "when", "else", "do", "throw"];
+const LITERAL_KEYWORDS: [&str; 7] = ["return", "const", "yield", "in", "await", "case", "when"];
+
+impl Formatter
+{
+	pub fn fix_trailing_commas(&self, content: &String) -> (String, i32)
+	{
+		if self.config.trailing_commas == TrailingCommas::Preserve
+		{
+			return (content.to_string(), 0);
+		}
+
+		let masked = scanner::mask_content(content);
+		let bytes = masked.as_bytes();
+
+		let mut stack: Vec<Opener> = Vec::new();
+		let mut edits: Vec<CommaEdit> = Vec::new();
+
+		for (i, b) in bytes.iter().enumerate()
+		{
+			match b
+			{
+				b'(' | b'[' | b'{' =>
+				{
+					stack.push(Opener { position: i, qualifies: self.is_comma_list(bytes, i), call: is_call(bytes, i), commas: 0, semicolons: 0 });
+				}
+				b',' =>
+				{
+					if let Some(opener) = stack.last_mut()
+					{
+						opener.commas += 1;
+					}
+				}
+				b';' =>
+				{
+					if let Some(opener) = stack.last_mut()
+					{
+						opener.semicolons += 1;
+					}
+				}
+				b')' | b']' | b'}' =>
+				{
+					if let Some(opener) = stack.pop()
+					{
+						if opener.qualifies && opener.semicolons == 0
+						{
+							if let Some(edit) = self.trailing_comma_edit(bytes, &opener, i)
+							{
+								edits.push(edit);
+							}
+						}
+					}
+				}
+				_ =>
+				{}
+			}
+		}
+
+		let mut fixed_content = content.to_string();
+		let count = edits.len() as i32;
+
+		edits.sort_by_key(|edit| match edit
+		{
+			CommaEdit::Insert(position) => *position,
+			CommaEdit::Remove(position, _) => *position,
+		});
+
+		for edit in edits.iter().rev()
+		{
+			match edit
+			{
+				CommaEdit::Insert(position) => fixed_content.insert(*position, ','),
+				CommaEdit::Remove(start, end) => fixed_content.replace_range(*start..*end, ""),
+			}
+		}
+
+		return (fixed_content, count);
+	}
+
+	fn trailing_comma_edit(&self, bytes: &[u8], opener: &Opener, close: usize) -> Option<CommaEdit>
+	{
+		let last = previous_significant(bytes, close)?;
+		if last <= opener.position
+		{
+			return None;
+		}
+
+		// A parenthesized expression turns into a record when given a trailing comma
+		let is_record = bytes[opener.position] == b'(' && !opener.call;
+		if is_record && opener.commas == 0
+		{
+			return None;
+		}
+
+		let is_multiline = bytes[opener.position..close].contains(&b'\n');
+		if is_multiline
+		{
+			let line_start = bytes[..close].iter().rposition(|b| *b == b'\n').map(|p| p + 1).unwrap_or(0);
+			let closer_on_own_line = bytes[line_start..close].iter().all(|b| b.is_ascii_whitespace());
+			if closer_on_own_line && bytes[last] != b','
+			{
+				if self.config.verbose
+				{
+					println!("Found missing trailing comma at byte {}", last + 1);
+				}
+				return Some(CommaEdit::Insert(last + 1));
+			}
+			return None;
+		}
+
+		if self.config.trailing_commas == TrailingCommas::MultilineOnly && bytes[last] == b',' && (!is_record || opener.commas >= 2)
+		{
+			return Some(CommaEdit::Remove(last, close));
+		}
+
+		return None;
+	}
+
+	fn is_comma_list(&self, bytes: &[u8], open: usize) -> bool
+	{
+		let previous = previous_significant(bytes, open);
+		let word = previous.map(|p| previous_word(bytes, p + 1)).unwrap_or("");
+
+		match bytes[open]
+		{
+			b'(' =>
+			{
+				return !NON_CALL_KEYWORDS.contains(&word);
+			}
+			b'[' => match previous
+			{
+				None => true,
+				Some(p) => !(is_identifier_byte(bytes[p]) || bytes[p] == b')' || bytes[p] == b']' || bytes[p] == b'!') || LITERAL_KEYWORDS.contains(&word),
+			},
+			_ =>
+			{
+				let header = statement_header(bytes, open);
+				if header.split_whitespace().any(|w| w == "enum")
+				{
+					return true;
+				}
+				if LITERAL_KEYWORDS.contains(&word) && word != "case"
+				{
+					return true;
+				}
+				match previous
+				{
+					None => false,
+					Some(p) => match bytes[p]
+					{
+						b'=' | b'(' | b'[' | b',' | b'?' => true,
+						b':' => !header.starts_with("case ") && !header.starts_with("default"),
+						b'>' => !header.split_whitespace().any(|w| w == "class" || w == "mixin" || w == "extension"),
+						_ => false,
+					},
+				}
+			}
+		}
+	}
+}
+
+fn previous_significant(bytes: &[u8], before: usize) -> Option<usize>
+{
+	return bytes[..before].iter().rposition(|b| !b.is_ascii_whitespace());
+}
+
+fn is_call(bytes: &[u8], open: usize) -> bool
+{
+	if bytes[open] != b'('
+	{
+		return false;
+	}
+	match previous_significant(bytes, open)
+	{
+		None => false,
+		Some(p) =>
+		{
+			let word = previous_word(bytes, p + 1);
+			return (is_identifier_byte(bytes[p]) && !NON_CALL_KEYWORDS.contains(&word)) || closes_type_arguments(bytes, p) || bytes[p] == b')' || bytes[p] == b']';
+		}
+	}
+}
+
+/// Tells the `>` of a generic call like `foo<T>(` apart from the ones of `=> (`, `>= (` and `a > (`
+fn closes_type_arguments(bytes: &[u8], close: usize) -> bool
+{
+	if bytes[close] != b'>' || (close > 0 && bytes[close - 1] == b'=')
+	{
+		return false;
+	}
+
+	let mut depth = 0;
+	for p in (0..=close).rev()
+	{
+		match bytes[p]
+		{
+			b'>' => depth += 1,
+			b'<' =>
+			{
+				depth -= 1;
+				if depth == 0
+				{
+					return previous_significant(bytes, p).is_some_and(|before| is_identifier_byte(bytes[before]));
+				}
+			}
+			b',' | b'.' | b'?' =>
+			{}
+			b if is_identifier_byte(b) || b.is_ascii_whitespace() =>
+			{}
+			_ => return false,
+		}
+	}
+
+	return false;
+}
+
+fn previous_word(bytes: &[u8], end: usize) -> &str
+{
+	let start = bytes[..end].iter().rposition(|b| !is_identifier_byte(*b)).map(|p| p + 1).unwrap_or(0);
+	return std::str::from_utf8(&bytes[start..end]).unwrap_or("");
+}
+
+fn statement_header(bytes: &[u8], open: usize) -> String
+{
+	let start = bytes[..open].iter().rposition(|b| *b == b';' || *b == b'{' || *b == b'}').map(|p| p + 1).unwrap_or(0);
+	return String::from_utf8_lossy(&bytes[start..open]).trim().to_string();
+}
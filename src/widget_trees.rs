@@ -0,0 +1,84 @@
+use crate::formatter::Formatter;
+use crate::source::Source;
+
+struct Opener
+{
+	bracket: u8,
+	/// Indentation the line holding the opener was given, and the one it had before
+	target: usize,
+	original: usize,
+}
+
+impl Formatter
+{
+	/// Indents everything within parentheses and square brackets one level deeper than the line opening them, and lines up closers with that line
+	pub fn indent_widget_trees(&self, content: &String) -> (String, i32)
+	{
+		if !self.config.flutter_widget_trees
+		{
+			return (content.to_string(), 0);
+		}
+
+		let source = Source::new(content);
+		let mut lines = source.owned_lines();
+		let masked_lines = &source.masked_lines;
+
+		let unit = self.config.indentation.size.max(1);
+		let mut incorrect_widget_indentations = 0;
+		let mut openers: Vec<Opener> = Vec::new();
+
+		for (line_number, line) in lines.iter_mut().enumerate()
+		{
+			let masked = masked_lines[line_number].trim();
+			let original = self.indentation_width(line);
+			let is_forbidden = source.protected.contains(line_number);
+
+			let leading_closers = masked.bytes().take_while(|b| matches!(b, b')' | b']' | b'}')).count();
+			let enclosing = openers.len().checked_sub(leading_closers.max(1)).map(|index| &openers[index]);
+
+			let target = match enclosing
+			{
+				_ if masked.is_empty() || is_forbidden => original,
+				Some(opener) if leading_closers > 0 => opener.target,
+				Some(opener) if opener.bracket != b'{' => opener.target + unit,
+				Some(opener) => (original as isize + opener.target as isize - opener.original as isize).max(0) as usize,
+				None => original,
+			};
+
+			if target != original && !masked.is_empty() && !is_forbidden
+			{
+				if self.config.verbose
+				{
+					println!("Found incorrect widget tree indentation - {}", line);
+				}
+
+				*line = self.reindent(line, target);
+				incorrect_widget_indentations += 1;
+			}
+
+			for b in masked.bytes()
+			{
+				match b
+				{
+					b'(' | b'[' | b'{' => openers.push(Opener { bracket: b, target, original }),
+					b')' | b']' | b'}' =>
+					{
+						openers.pop();
+					}
+					_ =>
+					{}
+				}
+			}
+		}
+
+		let mut cleaned_content = String::from("");
+
+		for line in lines
+		{
+			cleaned_content.push_str(line.as_str());
+			cleaned_content.push_str("\n");
+		}
+
+		return (cleaned_content, incorrect_widget_indentations);
+	}
+}